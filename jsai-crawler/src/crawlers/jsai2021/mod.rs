@@ -1,2 +1 @@
 pub mod kernel;
-pub mod models;
@@ -1,16 +1,23 @@
-use crate::crawlers::jsai2025::models::{Author2025, JsonData2025, Section2025, Session2025};
+use crate::shared::browser::goto_next_page;
 use crate::shared::utils::create_progress_bar;
-use crate::stats::ai::parse_authors;
-use crate::stats::models::Session;
-use anyhow::Result;
+use crate::stats::authors::{
+    fill_name_readings, fill_romanized_from_block, parse_authors_by_rules,
+};
+use crate::stats::models::{AuthorProvenance, Dataset, Section, Session, Year};
+use anyhow::{Result, anyhow};
+use fxhash::FxHashSet;
 use regex::Regex;
 use std::error::Error;
+use std::path::Path;
 use thirtyfour::prelude::*;
 use url::Url;
 
+const EVENT: &str = "jsai";
+const YEAR: Year = 2025;
+
 async fn collect_sections(
     driver: &WebDriver,
-) -> Result<Vec<Section2025>, Box<dyn Error + Send + Sync>> {
+) -> Result<Vec<Section>, Box<dyn Error + Send + Sync>> {
     let mut result_sections = Vec::new();
 
     let title_regex = Regex::new(r"^\[(?P<id>.+?)\](?P<title>.+)$")?;
@@ -43,14 +50,14 @@ async fn collect_sections(
             .text()
             .await?;
 
-        result_sections.push(Section2025::new(id, title, url, time));
+        result_sections.push(Section::new(id, title, url, time));
     }
     Ok(result_sections)
 }
 
 async fn extracx_session_url(
     driver: &WebDriver,
-    section: &Section2025,
+    section: &Section,
 ) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
     driver.goto(&section.url).await?;
     let mut session_urls = Vec::new();
@@ -70,11 +77,7 @@ async fn extracx_session_url(
     Ok(session_urls)
 }
 
-async fn parse_session(
-    driver: &WebDriver,
-    url: &str,
-    section: &Section2025,
-) -> Result<Session2025> {
+async fn parse_session(driver: &WebDriver, url: &str, section: &Section) -> Result<Session> {
     driver.goto(url).await?;
     let article = driver
         .find(By::Css("section article.sbject-single"))
@@ -107,18 +110,30 @@ async fn parse_session(
         Ok(abstract_element) => abstract_element.text().await?.trim().to_string(),
         Err(_) => String::new(),
     };
-    let authors_html = article
-        .find(By::Css("div.content p.personals.author"))
+    // papers with an English version show the author block a second time in Latin script
+    let mut author_blocks = Vec::new();
+    for element in article
+        .find_all(By::Css("div.content p.personals.author"))
         .await?
-        .text()
-        .await?
-        .trim()
-        .to_string();
-    let authors = parse_authors(&authors_html)?;
-    let authors: Vec<Author2025> = authors
-        .into_iter()
-        .map(|(name, affiliation)| Author2025::new(name, affiliation))
-        .collect();
+    {
+        author_blocks.push(element.text().await?.trim().to_string());
+    }
+    if author_blocks.is_empty() {
+        return Err(anyhow!("No author block found"));
+    }
+    let raw_authors = author_blocks.remove(0);
+    // authors the rules cannot handle are filled later by the `parse-authors` command
+    let parsed_authors = parse_authors_by_rules(&raw_authors)
+        .ok()
+        .map(|mut authors| {
+            for latin_block in &author_blocks {
+                fill_romanized_from_block(&mut authors, latin_block);
+            }
+            fill_name_readings(&mut authors);
+            authors
+        });
+    let author_provenance = parsed_authors.as_ref().map(|_| AuthorProvenance::rules());
+    let authors = parsed_authors.unwrap_or_default();
     let keywords: Vec<String> = match article.find(By::Css("div.content p.keyword")).await {
         Ok(keyword_element) => keyword_element
             .text()
@@ -131,16 +146,21 @@ async fn parse_session(
         Err(_) => Vec::new(),
     };
 
-    Ok(Session2025::new(
+    let mut session = Session::new(
+        EVENT.to_string(),
+        YEAR,
         id,
         title,
         url.to_string(),
         time,
         abstract_text,
         authors,
+        raw_authors,
         keywords,
         section.clone(),
-    ))
+    );
+    session.author_provenance = author_provenance;
+    Ok(session)
 }
 
 pub async fn crawl_jsai2025() -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -155,7 +175,9 @@ pub async fn crawl_jsai2025() -> Result<(), Box<dyn Error + Send + Sync>> {
         .await?;
 
     // collect sections for each day
-    let mut sections: Vec<Section2025> = Vec::new();
+    let mut sections: Vec<Section> = Vec::new();
+    let mut seen_section_urls: FxHashSet<String> = FxHashSet::default();
+    let mut unloaded_pages: Vec<String> = Vec::new();
     let days = vec!["20250527", "20250528", "20250529", "20250530"];
     let pb = create_progress_bar(days.len(), Some("Collecting sections".to_string()));
     for day in days {
@@ -167,26 +189,31 @@ pub async fn crawl_jsai2025() -> Result<(), Box<dyn Error + Send + Sync>> {
             .await?;
         loop {
             let ss = collect_sections(&driver).await?;
-            sections.extend(ss.clone());
-
-            match driver.find(By::Css("#pageNavHead li:last-child a")).await {
-                Ok(next_button) => {
-                    let next_text = next_button.text().await?;
-                    if next_text.contains("次へ") {
-                        // click the next button
-                        driver
-                            .action_chain()
-                            .click_element(&next_button)
-                            .perform()
-                            .await?;
-                        std::thread::sleep(std::time::Duration::from_secs(1)); // wait for the page to load
-                    } else {
-                        // no more pages
-                        break;
-                    }
+            let mut duplicated = 0;
+            for section in ss {
+                if seen_section_urls.insert(section.url.clone()) {
+                    sections.push(section);
+                } else {
+                    duplicated += 1;
                 }
-                Err(_) => {
-                    // no next button found, break the loop
+            }
+            if duplicated > 0 {
+                pb.println(format!(
+                    "Skipped {} duplicated sections on {}",
+                    duplicated,
+                    driver.current_url().await?
+                ));
+            }
+            pb.set_message(format!("Collected {} sections", sections.len()));
+
+            match goto_next_page(&driver).await {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    // keep the sections collected so far instead of losing the whole crawl
+                    let url = driver.current_url().await?.to_string();
+                    pb.println(format!("Warning: stopped paging at {}: {}", url, e));
+                    unloaded_pages.push(url);
                     break;
                 }
             }
@@ -196,7 +223,7 @@ pub async fn crawl_jsai2025() -> Result<(), Box<dyn Error + Send + Sync>> {
     pb.finish_with_message("Section collection completed");
 
     // collect sections
-    let mut sessions: Vec<Session2025> = Vec::new();
+    let mut sessions: Vec<Session> = Vec::new();
     let pb = create_progress_bar(sections.len(), Some("Collecting sessions".to_string()));
     for section in &sections {
         let session_urls = match extracx_session_url(&driver, section).await {
@@ -228,20 +255,18 @@ pub async fn crawl_jsai2025() -> Result<(), Box<dyn Error + Send + Sync>> {
     pb.finish_with_message("Session collection completed");
 
     // save sections and sessions into a JSON file
-    let json = serde_json::json!({
-        "sections": sections,
-        "sessions": sessions
-    });
-    std::fs::write("jsai2025.json", json.to_string())?;
+    let mut dataset = Dataset::new(sections, sessions);
+    dataset.unloaded_pages = unloaded_pages;
+    dataset.save(Path::new("jsai2025.json"))?;
 
     driver.quit().await?;
+    if !dataset.unloaded_pages.is_empty() {
+        return Err(format!(
+            "{} section pages did not load, the sections after them are missing from jsai2025.json: {}",
+            dataset.unloaded_pages.len(),
+            dataset.unloaded_pages.join(", ")
+        )
+        .into());
+    }
     Ok(())
 }
-
-pub fn load_sessions_from_json(
-    file_path: &str,
-) -> Result<Vec<Session>, Box<dyn Error + Send + Sync>> {
-    let data = std::fs::read_to_string(file_path)?;
-    let json_data: JsonData2025 = serde_json::from_str(&data)?;
-    Ok(json_data.sessions.into_iter().map(Session::from).collect())
-}
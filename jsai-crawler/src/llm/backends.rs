@@ -0,0 +1,149 @@
+use crate::llm::{ChatRequest, ChatResponse, LlmBackend, TokenUsage};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use derive_new::new;
+use openai_tools::{ChatCompletionRequestBody, OpenAI, ResponseFormat};
+use serde::Deserialize;
+
+fn response_format(request: &ChatRequest) -> Option<ResponseFormat> {
+    request
+        .json_schema
+        .as_ref()
+        .map(|schema| ResponseFormat::new("json_schema".to_string(), schema.clone()))
+}
+
+#[derive(Debug, Clone, new)]
+pub struct OpenAIBackend {
+    model_id: String,
+}
+
+#[async_trait]
+impl LlmBackend for OpenAIBackend {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let mut openai = OpenAI::new();
+        openai
+            .model_id(self.model_id.clone())
+            .messages(request.messages.clone())
+            .temperature(request.temperature);
+        if let Some(response_format) = response_format(request) {
+            openai.response_format(response_format);
+        }
+
+        let response = openai.chat().await?;
+        let choice = response
+            .choices
+            .first()
+            .ok_or_else(|| anyhow!("No choices returned from OpenAI API"))?;
+        Ok(ChatResponse {
+            content: choice.message.content.clone(),
+            usage: Some(TokenUsage {
+                prompt_tokens: response.usage.prompt_tokens,
+                completion_tokens: response.usage.completion_tokens,
+            }),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CompatibleMessage {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompatibleChoice {
+    message: CompatibleMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompatibleUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+/// Local servers omit several fields of the OpenAI response, so only the used ones are read.
+#[derive(Debug, Deserialize)]
+struct CompatibleResponse {
+    choices: Vec<CompatibleChoice>,
+    usage: Option<CompatibleUsage>,
+}
+
+#[derive(Debug, Clone, new)]
+pub struct OpenAICompatibleBackend {
+    base_url: String,
+    model_id: String,
+    api_key: Option<String>,
+}
+
+#[async_trait]
+impl LlmBackend for OpenAICompatibleBackend {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let mut body = ChatCompletionRequestBody::default();
+        body.model = self.model_id.clone();
+        body.messages = request.messages.clone();
+        body.temperature = Some(request.temperature);
+        body.response_format = response_format(request);
+
+        let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
+        let mut builder = request::Client::new()
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_string(&body)?);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response = builder.send().await?;
+        let status = response.status();
+        let content = response.text().await?;
+        if !status.is_success() {
+            return Err(anyhow!(
+                "Request to {} failed with status: {} CONTENT: {}",
+                url,
+                status,
+                content
+            ));
+        }
+
+        let response: CompatibleResponse = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse response: {}. CONTENT: {}", e, content))?;
+        let choice = response
+            .choices
+            .first()
+            .ok_or_else(|| anyhow!("No choices returned from {}", url))?;
+        Ok(ChatResponse {
+            content: choice.message.content.clone(),
+            usage: response.usage.map(|usage| TokenUsage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+            }),
+        })
+    }
+}
+
+/// Deterministic backend that always answers with the same content.
+#[derive(Debug, Clone, new)]
+pub struct MockBackend {
+    model_id: String,
+    response: String,
+}
+
+#[async_trait]
+impl LlmBackend for MockBackend {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse> {
+        Ok(ChatResponse {
+            content: self.response.clone(),
+            usage: None,
+        })
+    }
+}
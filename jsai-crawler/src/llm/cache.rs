@@ -0,0 +1,226 @@
+use crate::llm::{ChatRequest, ChatResponse, LlmBackend};
+use anyhow::Result;
+use async_trait::async_trait;
+use openai_tools::Message;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const DEFAULT_CACHE_DIR: &str = ".llm_cache";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub key: String,
+    pub model_id: String,
+    pub messages: Vec<Message>,
+    pub temperature: f32,
+    pub content: String,
+}
+
+/// Content-addressed store of chat responses, one JSON file per request.
+#[derive(Debug, Clone)]
+pub struct LlmCache {
+    dir: PathBuf,
+}
+
+impl LlmCache {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Hash of everything that determines the response: model id, prompt, input and output schema.
+    pub fn key(model_id: &str, request: &ChatRequest) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(model_id.as_bytes());
+        hasher.update(serde_json::to_string(&request.messages)?.as_bytes());
+        hasher.update(request.temperature.to_le_bytes());
+        if let Some(json_schema) = &request.json_schema {
+            hasher.update(serde_json::to_string(json_schema)?.as_bytes());
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<CacheEntry> {
+        let data = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    /// Write the entry through a temporary file so that an interrupted write leaves no partial entry.
+    pub fn put(&self, entry: &CacheEntry) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&entry.key);
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, serde_json::to_string(entry)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Every readable cached response; unreadable entries are skipped with a warning.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for file in fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let entry = fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|data| Ok(serde_json::from_str(&data)?));
+                match entry {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) => eprintln!(
+                        "Warning: skipped unreadable cache entry {}: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Remove every cached response and return the number of removed entries.
+    ///
+    /// Files are removed without being read, so that unreadable entries are cleared too.
+    pub fn clear(&self) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+        let mut count = 0;
+        for file in fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(path)?;
+                count += 1;
+            } else if path.to_string_lossy().ends_with(".json.tmp") {
+                // left behind by an interrupted `put`
+                fs::remove_file(path)?;
+            }
+        }
+        // other files in the directory are not ours to delete
+        if fs::read_dir(&self.dir)?.next().is_none() {
+            fs::remove_dir(&self.dir)?;
+        }
+        Ok(count)
+    }
+
+    /// Write every cached response into `output` as JSON Lines and return the number of entries.
+    pub fn export(&self, output: impl AsRef<Path>) -> Result<usize> {
+        let entries = self.entries()?;
+        let mut file = fs::File::create(output)?;
+        for entry in &entries {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        Ok(entries.len())
+    }
+}
+
+/// Backend decorator answering repeated requests from an [`LlmCache`].
+pub struct CachedBackend {
+    inner: Box<dyn LlmBackend>,
+    cache: LlmCache,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachedBackend {
+    pub fn new(inner: Box<dyn LlmBackend>, cache: LlmCache) -> Self {
+        Self {
+            inner,
+            cache,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmBackend for CachedBackend {
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let key = LlmCache::key(self.model_id(), request)?;
+        if let Some(entry) = self.cache.get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(ChatResponse {
+                content: entry.content,
+                usage: None,
+            });
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let response = self.inner.chat(request).await?;
+        // the response is paid for already; a full disk should not throw it away
+        if let Err(e) = self.cache.put(&CacheEntry {
+            key,
+            model_id: self.model_id().to_string(),
+            messages: request.messages.clone(),
+            temperature: request.temperature,
+            content: response.content.clone(),
+        }) {
+            eprintln!("Warning: failed to cache the LLM response: {}", e);
+        }
+        Ok(response)
+    }
+
+    fn summary(&self) -> Vec<String> {
+        let mut summary = self.inner.summary();
+        summary.push(format!(
+            "LLM cache: {} hits, {} misses",
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed)
+        ));
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str) -> CacheEntry {
+        CacheEntry {
+            key: key.to_string(),
+            model_id: "mock".to_string(),
+            messages: Vec::new(),
+            temperature: 1.0,
+            content: format!("{{\"key\": \"{}\"}}", key),
+        }
+    }
+
+    #[test]
+    fn unreadable_entries_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("jsai-llm-cache-{}", std::process::id()));
+        let cache = LlmCache::new(&dir);
+        cache.put(&entry("a")).unwrap();
+        cache.put(&entry("b")).unwrap();
+        fs::write(dir.join("c.json"), "{\"key\": \"c\", \"mod").unwrap();
+        fs::write(dir.join("d.json.tmp"), "{").unwrap();
+
+        let mut keys = cache
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.key)
+            .collect::<Vec<String>>();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(cache.get("a").unwrap().content, entry("a").content);
+
+        assert_eq!(cache.clear().unwrap(), 3);
+        assert!(!dir.exists());
+    }
+}
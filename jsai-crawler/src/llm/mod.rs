@@ -0,0 +1,191 @@
+pub mod backends;
+pub mod cache;
+pub mod prompt;
+pub mod usage;
+
+use crate::llm::backends::{MockBackend, OpenAIBackend, OpenAICompatibleBackend};
+use crate::llm::cache::{CachedBackend, DEFAULT_CACHE_DIR, LlmCache};
+use crate::llm::usage::{Budget, MeteredBackend, Pricing};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use dotenvy::dotenv;
+use openai_tools::{Message, json_schema::JsonSchema};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub messages: Vec<Message>,
+    pub temperature: f32,
+    pub json_schema: Option<JsonSchema>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChatResponse {
+    pub content: String,
+    /// Token counts, when the backend reports them
+    pub usage: Option<TokenUsage>,
+}
+
+/// A chat model that `parse_authors` and the other LLM tasks can talk to.
+#[async_trait]
+pub trait LlmBackend: Send + Sync {
+    fn model_id(&self) -> &str;
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse>;
+
+    /// Lines reported at the end of a run, e.g. cache statistics.
+    fn summary(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LlmBackendKind {
+    /// The OpenAI API
+    OpenAI,
+    /// Any server implementing `/chat/completions`, e.g. llama.cpp or Ollama
+    OpenAICompatible,
+    /// Returns a fixed response without network access
+    Mock,
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    pub kind: LlmBackendKind,
+    pub model_id: String,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub mock_response: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    pub pricing: Option<Pricing>,
+    pub budget: Budget,
+    pub usage_log: Option<PathBuf>,
+}
+
+impl LlmConfig {
+    /// Read the backend configuration from the environment (and `.env`).
+    ///
+    /// - `LLM_BACKEND`: `openai` (default), `openai-compatible` or `mock`
+    /// - `LLM_MODEL_ID` or `OPENAI_MODEL_ID`: the model to use
+    /// - `LLM_BASE_URL`: base URL of an OpenAI-compatible server, e.g. `http://localhost:8080/v1`
+    /// - `LLM_API_KEY` or `OPENAI_API_KEY`: the API key, optional for OpenAI-compatible servers
+    /// - `LLM_MOCK_RESPONSE`: the response returned by the mock backend
+    /// - `LLM_CACHE_DIR`: directory of the response cache, `.llm_cache` by default
+    /// - `LLM_CACHE`: set to `off` to disable the response cache
+    /// - `LLM_PRICE_PROMPT_PER_1M`, `LLM_PRICE_COMPLETION_PER_1M`: USD per million tokens, for cost estimates
+    /// - `LLM_BUDGET_TOKENS`, `LLM_BUDGET_USD`: stop issuing calls once the run has used this much
+    /// - `LLM_USAGE_LOG`: append the usage of every call to this JSON Lines file
+    pub fn from_env() -> Result<Self> {
+        dotenv().ok();
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        let number = |key: &str| -> Result<Option<f64>> {
+            var(key)
+                .map(|v| {
+                    v.parse::<f64>()
+                        .map_err(|e| anyhow!("Invalid {}: {}", key, e))
+                })
+                .transpose()
+        };
+
+        let kind = match var("LLM_BACKEND").as_deref() {
+            None | Some("openai") => LlmBackendKind::OpenAI,
+            Some("openai-compatible") => LlmBackendKind::OpenAICompatible,
+            Some("mock") => LlmBackendKind::Mock,
+            Some(other) => return Err(anyhow!("Unknown LLM_BACKEND: {}", other)),
+        };
+        let model_id = match (
+            var("LLM_MODEL_ID").or_else(|| var("OPENAI_MODEL_ID")),
+            &kind,
+        ) {
+            (Some(model_id), _) => model_id,
+            (None, LlmBackendKind::Mock) => "mock".to_string(),
+            (None, _) => return Err(anyhow!("LLM_MODEL_ID or OPENAI_MODEL_ID must be set")),
+        };
+
+        Ok(Self {
+            kind,
+            model_id,
+            base_url: var("LLM_BASE_URL"),
+            api_key: var("LLM_API_KEY").or_else(|| var("OPENAI_API_KEY")),
+            mock_response: var("LLM_MOCK_RESPONSE"),
+            cache_dir: match var("LLM_CACHE").as_deref() {
+                Some("off") => None,
+                _ => Some(PathBuf::from(
+                    var("LLM_CACHE_DIR").unwrap_or_else(|| DEFAULT_CACHE_DIR.to_string()),
+                )),
+            },
+            pricing: match (
+                number("LLM_PRICE_PROMPT_PER_1M")?,
+                number("LLM_PRICE_COMPLETION_PER_1M")?,
+            ) {
+                (None, None) => None,
+                (prompt, completion) => Some(Pricing {
+                    prompt_per_1m: prompt.unwrap_or_default(),
+                    completion_per_1m: completion.unwrap_or_default(),
+                }),
+            },
+            budget: Budget {
+                max_tokens: number("LLM_BUDGET_TOKENS")?.map(|v| v as u64),
+                max_cost: number("LLM_BUDGET_USD")?,
+            },
+            usage_log: var("LLM_USAGE_LOG").map(PathBuf::from),
+        })
+    }
+}
+
+/// Create the configured backend, metered for usage and budget, and wrapped with the response
+/// cache unless it is disabled. Cache hits are not metered.
+pub fn create_backend(config: &LlmConfig) -> Result<Box<dyn LlmBackend>> {
+    if config.budget.max_cost.is_some() && config.pricing.is_none() {
+        return Err(anyhow!(
+            "LLM_BUDGET_USD requires LLM_PRICE_PROMPT_PER_1M and LLM_PRICE_COMPLETION_PER_1M"
+        ));
+    }
+    let backend: Box<dyn LlmBackend> = Box::new(MeteredBackend::new(
+        create_uncached_backend(config)?,
+        config.pricing,
+        config.budget,
+        config.usage_log.clone(),
+    ));
+    Ok(match &config.cache_dir {
+        Some(cache_dir) if config.kind != LlmBackendKind::Mock => {
+            Box::new(CachedBackend::new(backend, LlmCache::new(cache_dir)))
+        }
+        _ => backend,
+    })
+}
+
+fn create_uncached_backend(config: &LlmConfig) -> Result<Box<dyn LlmBackend>> {
+    match config.kind {
+        LlmBackendKind::OpenAI => {
+            // openai_tools reads the key by itself and panics when it is missing
+            if std::env::var("OPENAI_API_KEY").is_err() {
+                return Err(anyhow!("OPENAI_API_KEY must be set for the OpenAI backend"));
+            }
+            Ok(Box::new(OpenAIBackend::new(config.model_id.clone())))
+        }
+        LlmBackendKind::OpenAICompatible => {
+            let base_url = config.base_url.clone().ok_or_else(|| {
+                anyhow!("LLM_BASE_URL must be set for the OpenAI-compatible backend")
+            })?;
+            Ok(Box::new(OpenAICompatibleBackend::new(
+                base_url,
+                config.model_id.clone(),
+                config.api_key.clone(),
+            )))
+        }
+        LlmBackendKind::Mock => Ok(Box::new(MockBackend::new(
+            config.model_id.clone(),
+            config
+                .mock_response
+                .clone()
+                .unwrap_or_else(|| r#"{"authors": []}"#.to_string()),
+        ))),
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{Result, anyhow};
+use openai_tools::Message;
+use openai_tools::json_schema::{ItemType, JsonItem, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const PARSE_AUTHORS_PROMPT: &str = include_str!("../../prompts/parse_authors/v2.toml");
+
+fn string_type() -> String {
+    "string".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaItem {
+    pub name: String,
+    pub description: String,
+    /// JSON type of the field, `string` by default
+    #[serde(rename = "type", default = "string_type")]
+    pub item_type: String,
+    /// Fields of the objects of an `array` field
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<SchemaItem>,
+}
+
+impl SchemaItem {
+    fn item_type(&self) -> ItemType {
+        let mut item_type = ItemType::new(self.item_type.clone(), Some(self.description.clone()));
+        if !self.items.is_empty() {
+            item_type.items = Some(Box::new(object_of(&self.items)));
+        }
+        item_type
+    }
+}
+
+fn object_of(items: &[SchemaItem]) -> JsonItem {
+    let mut object = JsonItem::default();
+    for item in items {
+        object.add_property(item.name.clone(), item.item_type());
+    }
+    object
+}
+
+/// JSON schema of the response: an object holding an array of objects, whose fields are strings
+/// unless they give another type; `array` fields hold objects in turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaTemplate {
+    pub name: String,
+    pub array: String,
+    pub items: Vec<SchemaItem>,
+}
+
+/// Prompt of an LLM task, loaded from a TOML file under `prompts/`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub version: String,
+    pub temperature: f32,
+    pub system: String,
+    /// User message, `{text}` is replaced with the input
+    pub user: String,
+    pub schema: Option<SchemaTemplate>,
+}
+
+impl PromptTemplate {
+    pub fn from_toml(data: &str) -> Result<Self> {
+        let template: Self = toml::from_str(data)?;
+        if !template.user.contains("{text}") {
+            return Err(anyhow!(
+                "Prompt {}@{} has no {{text}} placeholder",
+                template.id,
+                template.version
+            ));
+        }
+        Ok(template)
+    }
+
+    pub fn load(file_path: &Path) -> Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(file_path)?)
+            .map_err(|e| anyhow!("Failed to load prompt {}: {}", file_path.display(), e))
+    }
+
+    /// The bundled prompt of `parse_authors`.
+    pub fn parse_authors() -> Self {
+        Self::from_toml(PARSE_AUTHORS_PROMPT).expect("Bundled parse_authors prompt is invalid")
+    }
+
+    /// The prompt in `file_path`, or the bundled prompt of `parse_authors` when no path is given.
+    pub fn parse_authors_or_load(file_path: Option<&Path>) -> Result<Self> {
+        match file_path {
+            Some(file_path) => Self::load(file_path),
+            None => Ok(Self::parse_authors()),
+        }
+    }
+
+    pub fn messages(&self, text: &str) -> Vec<Message> {
+        vec![
+            Message::new(String::from("system"), self.system.clone()),
+            Message::new(String::from("user"), self.user.replace("{text}", text)),
+        ]
+    }
+
+    pub fn json_schema(&self) -> Option<JsonSchema> {
+        self.schema.as_ref().map(|schema| {
+            let mut json_schema = JsonSchema::new(schema.name.clone());
+            json_schema
+                .schema
+                .add_array(schema.array.clone(), object_of(&schema.items));
+            json_schema
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_prompt_asks_for_indexed_affiliations() {
+        let prompt = PromptTemplate::parse_authors();
+        assert_eq!(prompt.version, "v2");
+        let schema = serde_json::to_value(prompt.json_schema().unwrap().schema).unwrap();
+        let author = &schema["properties"]["authors"]["items"];
+        let affiliation = &author["properties"]["affiliations"]["items"];
+        assert_eq!(author["properties"]["affiliations"]["type"], "array");
+        assert_eq!(affiliation["properties"]["name"]["type"], "string");
+        assert_eq!(affiliation["properties"]["index"]["type"], "integer");
+    }
+
+    #[test]
+    fn v1_prompt_still_loads() {
+        let prompt =
+            PromptTemplate::from_toml(include_str!("../../prompts/parse_authors/v1.toml")).unwrap();
+        let schema = serde_json::to_value(prompt.json_schema().unwrap().schema).unwrap();
+        assert_eq!(
+            schema["properties"]["authors"]["items"]["properties"]["affiliation"]["type"],
+            "string"
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use crate::llm::{ChatRequest, ChatResponse, LlmBackend, TokenUsage};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+use tokio::sync::Notify;
+
+/// Price of a model in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    pub prompt_per_1m: f64,
+    pub completion_per_1m: f64,
+}
+
+impl Pricing {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt_per_1m
+            + usage.completion_tokens as f64 * self.completion_per_1m)
+            / 1_000_000.0
+    }
+}
+
+/// Limits of a run; calls are refused once one of them is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    pub max_tokens: Option<u64>,
+    pub max_cost: Option<f64>,
+}
+
+impl Budget {
+    fn is_set(&self) -> bool {
+        self.max_tokens.is_some() || self.max_cost.is_some()
+    }
+}
+
+/// Error of a call refused by the [`Budget`]; every later call would be refused too, so callers
+/// stop instead of moving on to the next input.
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded(pub String);
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LLM budget exceeded: {}", self.0)
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub model_id: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub latency_ms: u64,
+    pub success: bool,
+}
+
+/// Recorded calls and calls in flight, behind one lock so that checking the budget and reserving
+/// a call happen together.
+#[derive(Debug, Default)]
+struct Meter {
+    records: Vec<UsageRecord>,
+    in_flight: u64,
+}
+
+impl Meter {
+    fn total_usage(&self) -> TokenUsage {
+        self.records
+            .iter()
+            .fold(TokenUsage::default(), |total, r| TokenUsage {
+                prompt_tokens: total.prompt_tokens + r.prompt_tokens,
+                completion_tokens: total.completion_tokens + r.completion_tokens,
+            })
+    }
+
+    /// Usage of the most expensive call so far, the estimate of a call in flight.
+    fn largest_call(&self) -> Option<TokenUsage> {
+        self.records
+            .iter()
+            .filter(|r| r.success)
+            .max_by_key(|r| r.prompt_tokens + r.completion_tokens)
+            .map(|r| TokenUsage {
+                prompt_tokens: r.prompt_tokens,
+                completion_tokens: r.completion_tokens,
+            })
+    }
+}
+
+/// Reservation of a granted call, released when dropped, so that a call whose future is cancelled
+/// does not keep waiting calls blocked.
+struct InFlight<'a> {
+    backend: &'a MeteredBackend,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.backend.meter.lock().unwrap().in_flight -= 1;
+        self.backend.finished.notify_waiters();
+    }
+}
+
+enum Reservation {
+    Granted,
+    /// The budget may not cover the calls in flight; ask again when one of them has finished
+    Wait,
+    Refused(String),
+}
+
+/// Backend decorator collecting the token usage and latency of every call and enforcing a [`Budget`].
+///
+/// A call is only issued when the budget covers it together with the calls in flight, each
+/// estimated by the most expensive call so far; until a call has finished, calls go out one at a
+/// time. A run can therefore overshoot the budget by at most one call, or by the amount a call
+/// exceeds the estimate.
+pub struct MeteredBackend {
+    inner: Box<dyn LlmBackend>,
+    pricing: Option<Pricing>,
+    budget: Budget,
+    usage_log: Option<PathBuf>,
+    meter: Mutex<Meter>,
+    /// Notified whenever a call in flight finishes
+    finished: Notify,
+    refused: AtomicU64,
+    warned_missing_usage: AtomicBool,
+}
+
+impl MeteredBackend {
+    pub fn new(
+        inner: Box<dyn LlmBackend>,
+        pricing: Option<Pricing>,
+        budget: Budget,
+        usage_log: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            inner,
+            pricing,
+            budget,
+            usage_log,
+            meter: Mutex::new(Meter::default()),
+            finished: Notify::new(),
+            refused: AtomicU64::new(0),
+            warned_missing_usage: AtomicBool::new(false),
+        }
+    }
+
+    pub fn records(&self) -> Vec<UsageRecord> {
+        self.meter.lock().unwrap().records.clone()
+    }
+
+    pub fn total_usage(&self) -> TokenUsage {
+        self.meter.lock().unwrap().total_usage()
+    }
+
+    pub fn total_cost(&self) -> Option<f64> {
+        self.pricing
+            .map(|pricing| pricing.cost(&self.total_usage()))
+    }
+
+    /// Why the budget does not cover `usage`, if it does not.
+    fn exceeds_budget(&self, usage: &TokenUsage) -> Option<String> {
+        let tokens = usage.prompt_tokens + usage.completion_tokens;
+        if let Some(max_tokens) = self.budget.max_tokens
+            && tokens >= max_tokens
+        {
+            return Some(format!("{} of {} tokens used", tokens, max_tokens));
+        }
+        if let (Some(max_cost), Some(pricing)) = (self.budget.max_cost, self.pricing) {
+            let cost = pricing.cost(usage);
+            if cost >= max_cost {
+                return Some(format!("${:.4} of ${:.4} spent", cost, max_cost));
+            }
+        }
+        None
+    }
+
+    fn reserve(&self) -> Reservation {
+        let mut meter = self.meter.lock().unwrap();
+        if self.budget.is_set() {
+            let used = meter.total_usage();
+            if let Some(reason) = self.exceeds_budget(&used) {
+                return Reservation::Refused(reason);
+            }
+            if meter.in_flight > 0 {
+                let Some(largest) = meter.largest_call() else {
+                    return Reservation::Wait;
+                };
+                let calls = meter.in_flight + 1;
+                let projected = TokenUsage {
+                    prompt_tokens: used.prompt_tokens + largest.prompt_tokens * calls,
+                    completion_tokens: used.completion_tokens + largest.completion_tokens * calls,
+                };
+                if self.exceeds_budget(&projected).is_some() {
+                    return Reservation::Wait;
+                }
+            }
+        }
+        meter.in_flight += 1;
+        Reservation::Granted
+    }
+
+    fn record(&self, record: UsageRecord) {
+        self.meter.lock().unwrap().records.push(record.clone());
+        // the response is paid for already; a failed log write should not throw it away
+        if let Err(e) = self.append_to_log(&record) {
+            eprintln!("Warning: failed to write the LLM usage log: {}", e);
+        }
+    }
+
+    fn append_to_log(&self, record: &UsageRecord) -> Result<()> {
+        if let Some(usage_log) = &self.usage_log {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(usage_log)?;
+            writeln!(file, "{}", serde_json::to_string(record)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LlmBackend for MeteredBackend {
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse> {
+        let in_flight = loop {
+            // created before the check, so that a call finishing in between is not missed
+            let finished = self.finished.notified();
+            match self.reserve() {
+                Reservation::Granted => break InFlight { backend: self },
+                Reservation::Wait => finished.await,
+                Reservation::Refused(reason) => {
+                    self.refused.fetch_add(1, Ordering::Relaxed);
+                    return Err(BudgetExceeded(reason).into());
+                }
+            }
+        };
+
+        let started = Instant::now();
+        let response = self.inner.chat(request).await;
+        if let Ok(ChatResponse { usage: None, .. }) = &response
+            && self.budget.is_set()
+            && !self.warned_missing_usage.swap(true, Ordering::Relaxed)
+        {
+            eprintln!(
+                "Warning: {} does not report token usage, so the LLM budget cannot be enforced",
+                self.model_id()
+            );
+        }
+        let usage = response
+            .as_ref()
+            .ok()
+            .and_then(|r| r.usage)
+            .unwrap_or_default();
+        self.record(UsageRecord {
+            model_id: self.model_id().to_string(),
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            latency_ms: started.elapsed().as_millis() as u64,
+            success: response.is_ok(),
+        });
+        drop(in_flight);
+        response
+    }
+
+    fn summary(&self) -> Vec<String> {
+        let mut summary = self.inner.summary();
+        let records = self.records();
+        let failed = records.iter().filter(|r| !r.success).count();
+        let usage = self.total_usage();
+        let average_latency = if records.is_empty() {
+            0
+        } else {
+            records.iter().map(|r| r.latency_ms).sum::<u64>() / records.len() as u64
+        };
+        let mut line = format!(
+            "LLM usage ({}): {} calls ({} failed), {} prompt + {} completion tokens, avg latency {} ms",
+            self.model_id(),
+            records.len(),
+            failed,
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            average_latency
+        );
+        if let Some(cost) = self.total_cost() {
+            line.push_str(&format!(", estimated cost ${:.4}", cost));
+        }
+        summary.push(line);
+
+        let refused = self.refused.load(Ordering::Relaxed);
+        if refused > 0 {
+            summary.push(format!(
+                "LLM budget exceeded: {} calls were not issued",
+                refused
+            ));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    /// Answers every call with 100 tokens after a short delay, so that calls overlap.
+    struct FixedUsageBackend;
+
+    #[async_trait]
+    impl LlmBackend for FixedUsageBackend {
+        fn model_id(&self) -> &str {
+            "fixed"
+        }
+
+        async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse> {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            Ok(ChatResponse {
+                content: String::new(),
+                usage: Some(TokenUsage {
+                    prompt_tokens: 60,
+                    completion_tokens: 40,
+                }),
+            })
+        }
+    }
+
+    /// Never answers within the tests.
+    struct HangingBackend;
+
+    #[async_trait]
+    impl LlmBackend for HangingBackend {
+        fn model_id(&self) -> &str {
+            "hanging"
+        }
+
+        async fn chat(&self, _request: &ChatRequest) -> Result<ChatResponse> {
+            tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+            unreachable!()
+        }
+    }
+
+    fn request() -> ChatRequest {
+        ChatRequest {
+            messages: Vec::new(),
+            temperature: 0.0,
+            json_schema: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_calls_release_their_reservation() {
+        let backend = MeteredBackend::new(
+            Box::new(HangingBackend),
+            None,
+            Budget {
+                max_tokens: Some(350),
+                max_cost: None,
+            },
+            None,
+        );
+        let request = request();
+        let cancelled =
+            tokio::time::timeout(std::time::Duration::from_millis(10), backend.chat(&request))
+                .await;
+        assert!(cancelled.is_err());
+        assert_eq!(backend.meter.lock().unwrap().in_flight, 0);
+        assert!(matches!(backend.reserve(), Reservation::Granted));
+    }
+
+    #[tokio::test]
+    async fn responses_survive_a_failed_usage_log_write() {
+        let backend = MeteredBackend::new(
+            Box::new(FixedUsageBackend),
+            None,
+            Budget::default(),
+            Some(PathBuf::from("/nonexistent/usage.jsonl")),
+        );
+        assert!(backend.chat(&request()).await.is_ok());
+        assert_eq!(backend.records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_stay_within_the_budget() {
+        let backend = MeteredBackend::new(
+            Box::new(FixedUsageBackend),
+            None,
+            Budget {
+                max_tokens: Some(350),
+                max_cost: None,
+            },
+            None,
+        );
+        let request = request();
+        let results = futures::stream::iter(0..10)
+            .map(|_| backend.chat(&request))
+            .buffer_unordered(8)
+            .collect::<Vec<_>>()
+            .await;
+
+        let refused = results
+            .iter()
+            .filter(|r| {
+                r.as_ref()
+                    .is_err_and(|e| e.downcast_ref::<BudgetExceeded>().is_some())
+            })
+            .count();
+        assert_eq!(results.len() - refused, 4);
+        let usage = backend.total_usage();
+        assert_eq!(usage.prompt_tokens + usage.completion_tokens, 400);
+    }
+}
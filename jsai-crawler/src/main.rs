@@ -1,4 +1,5 @@
 pub mod crawlers;
+pub mod llm;
 pub mod mecab;
 pub mod shared;
 pub mod stats;
@@ -8,7 +9,27 @@ use crate::crawlers::{
     jsai2023::kernel::crawl_jsai2023, jsai2024::kernel::crawl_jsai2024,
     jsai2025::kernel::crawl_jsai2025,
 };
-use crate::stats::models::Stats;
+use crate::llm::cache::{DEFAULT_CACHE_DIR, LlmCache};
+use crate::llm::prompt::PromptTemplate;
+use crate::llm::{LlmConfig, create_backend};
+use crate::stats::affiliations::{AffiliationDictionary, AffiliationLevel};
+use crate::stats::audit::{
+    AuditSummary, load_audit_records, run_audit, summarize_audit, verdicts_path,
+};
+use crate::stats::authors::fill_name_readings;
+use crate::stats::authors::parse_authors_in_dataset;
+use crate::stats::bibliography::{BibFormat, format_bibliography, select_sessions};
+use crate::stats::db::{QueryFormat, export_database, query_database};
+use crate::stats::eval::{
+    AuthorParserKind, evaluate_author_parser, load_gold_examples, sample_gold_candidates,
+    write_gold_examples,
+};
+use crate::stats::export::{ExportFormat, ExportTable, export_sessions};
+use crate::stats::identity::{load_overrides, resolve_author_ids};
+use crate::stats::loader::{load_sessions, load_sessions_by_year};
+use crate::stats::models::{Dataset, Stats};
+use crate::stats::researchers::{DEFAULT_MIN_SIMILARITY, link_researcher_ids, load_researchers};
+use crate::stats::validate::{LintCheck, lint_dataset, parse_threshold};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -33,17 +54,239 @@ enum SubCommands {
     CrawlJsai2025,
     #[command(name = "analyze")]
     Analyze(AnalyzeArgs),
+    #[command(name = "cache")]
+    Cache(CacheArgs),
+    #[command(name = "parse-authors")]
+    ParseAuthors(ParseAuthorsArgs),
+    #[command(name = "eval-authors")]
+    EvalAuthors(EvalAuthorsArgs),
+    #[command(name = "resolve-authors")]
+    ResolveAuthors(ResolveAuthorsArgs),
+    #[command(name = "link-ids")]
+    LinkIds(LinkIdsArgs),
+    #[command(name = "audit")]
+    Audit(AuditArgs),
+    #[command(name = "db")]
+    Db(DbArgs),
+    #[command(name = "export")]
+    Export(ExportArgs),
+    #[command(name = "cite")]
+    Cite(CiteArgs),
+    #[command(name = "validate")]
+    Validate(ValidateArgs),
+    #[command(name = "sample-gold")]
+    SampleGold(SampleGoldArgs),
 }
 
 #[derive(Parser, Debug)]
 struct AnalyzeArgs {
-    /// Path to the JSAI 2025 data file
-    #[arg(short, long)]
-    data: String,
+    /// Dataset files (plain or zstd-compressed JSON) or directories of them
+    #[arg(short, long, num_args = 1.., required = true)]
+    data: Vec<String>,
+    /// Only analyze this year; by default every year found in the datasets is analyzed
     #[arg(short, long)]
-    year: u32,
+    year: Option<u32>,
     #[arg(short, long, default_value = "output")]
     output_dir: Option<String>,
+    /// CSV file of additional affiliation aliases with the header `alias,canonical,org_type`
+    #[arg(long)]
+    affiliation_aliases: Option<String>,
+    /// Level at which the most frequent affiliations are reported
+    #[arg(long, value_enum, default_value_t = AffiliationLevel::Organization)]
+    affiliation_level: AffiliationLevel,
+}
+
+#[derive(Parser, Debug)]
+struct ParseAuthorsArgs {
+    /// Path to the dataset written by a crawl, updated in place
+    dataset: String,
+    /// Number of sessions parsed concurrently
+    #[arg(short, long, default_value_t = 4)]
+    concurrency: usize,
+    /// Re-parse sessions that already have authors
+    #[arg(short, long)]
+    force: bool,
+    /// Prompt template file, the bundled `prompts/parse_authors/v2.toml` by default
+    #[arg(short, long)]
+    prompt: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct EvalAuthorsArgs {
+    /// Path to the gold-labelled author blocks (JSON Lines)
+    #[arg(short, long, default_value = "eval/gold_authors.jsonl")]
+    gold: String,
+    /// Author parser to evaluate
+    #[arg(short, long, value_enum, default_value_t = AuthorParserKind::Auto)]
+    parser: AuthorParserKind,
+    /// Write the report as JSON to this path
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Print the examples the parser got wrong
+    #[arg(short, long)]
+    verbose: bool,
+    /// Prompt template file, the bundled `prompts/parse_authors/v2.toml` by default
+    #[arg(long)]
+    prompt: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ResolveAuthorsArgs {
+    /// Datasets of all years, updated in place with the resolved author ids
+    #[arg(required = true)]
+    datasets: Vec<String>,
+    /// CSV file of manual assignments with the header `name,affiliation,author_id`
+    #[arg(long)]
+    overrides: Option<String>,
+    /// Path of the resolved author registry
+    #[arg(short, long, default_value = "author_registry.json")]
+    output: String,
+}
+
+#[derive(Parser, Debug)]
+struct LinkIdsArgs {
+    /// Datasets updated in place with the linked ORCID and researchmap ids
+    #[arg(required = true)]
+    datasets: Vec<String>,
+    /// Researcher mapping file (.csv or .jsonl) with the fields `name,name_en,affiliation,orcid,researchmap_id`
+    #[arg(short, long)]
+    mapping: String,
+    /// Minimum affiliation similarity for a link
+    #[arg(long, default_value_t = DEFAULT_MIN_SIMILARITY)]
+    min_similarity: f64,
+}
+
+#[derive(Parser, Debug)]
+struct AuditArgs {
+    /// Dataset to audit
+    dataset: String,
+    /// Number of sessions to sample
+    #[arg(short, long, default_value_t = 50)]
+    sample: usize,
+    /// Seed of the sample; the same seed draws the same sessions
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+    /// Verdicts file (JSON Lines), `jsai2025.audit.jsonl` for `jsai2025.json(.zst)` by default
+    #[arg(short, long)]
+    verdicts: Option<String>,
+    /// Name recorded with the verdicts, `$USER` by default
+    #[arg(short, long)]
+    reviewer: Option<String>,
+    /// Only print the error rate of the recorded verdicts
+    #[arg(long)]
+    report: bool,
+}
+
+#[derive(Parser, Debug)]
+struct CacheArgs {
+    #[command(subcommand)]
+    command: CacheCommands,
+    /// Directory of the LLM response cache
+    #[arg(short, long, default_value = DEFAULT_CACHE_DIR)]
+    dir: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommands {
+    /// Remove all cached LLM responses
+    #[command(name = "clear")]
+    Clear,
+    /// Export all cached LLM responses as JSON Lines
+    #[command(name = "export")]
+    Export {
+        #[arg(short, long, default_value = "llm_cache.jsonl")]
+        output: String,
+    },
+}
+
+#[derive(Parser, Debug)]
+struct ExportArgs {
+    /// Dataset files or directories of them
+    #[arg(num_args = 1.., required = true)]
+    datasets: Vec<String>,
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Csv)]
+    format: ExportFormat,
+    #[arg(short, long, default_value = "export")]
+    output_dir: String,
+    /// Tables to write, comma-separated
+    #[arg(short, long, value_enum, value_delimiter = ',', default_values_t = [ExportTable::Sessions, ExportTable::Authors, ExportTable::Keywords])]
+    tables: Vec<ExportTable>,
+    /// Columns to write, comma-separated, e.g. `year,session_id,title`; all columns by default
+    #[arg(short, long, value_delimiter = ',')]
+    columns: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct CiteArgs {
+    /// Dataset files or directories of them
+    #[arg(num_args = 1.., required = true)]
+    datasets: Vec<String>,
+    #[arg(short, long, value_enum, default_value_t = BibFormat::Bibtex)]
+    format: BibFormat,
+    /// Session ids to cite, comma-separated, e.g. `1A3-GS-2-01`
+    #[arg(short, long, value_delimiter = ',')]
+    ids: Vec<String>,
+    /// Only cite sessions whose title, abstract or keywords contain this text
+    #[arg(short, long)]
+    query: Option<String>,
+    /// Output file; the bibliography is printed when omitted
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct ValidateArgs {
+    /// Dataset written by a crawl
+    dataset: String,
+    /// Highest tolerated share of offending sessions for a check, e.g. `no-authors=0.1`; repeatable
+    #[arg(short, long, value_parser = parse_threshold)]
+    threshold: Vec<(LintCheck, f64)>,
+}
+
+#[derive(Parser, Debug)]
+struct SampleGoldArgs {
+    /// Dataset files or directories of them
+    #[arg(num_args = 1.., required = true)]
+    datasets: Vec<String>,
+    /// Number of author blocks to draw
+    #[arg(short, long, default_value_t = 30)]
+    size: usize,
+    /// Seed of the sample; the same seed draws the same author blocks
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+    /// Candidates file (JSON Lines) to review before appending it to the gold file
+    #[arg(short, long, default_value = "eval/gold_candidates.jsonl")]
+    output: String,
+}
+
+#[derive(Parser, Debug)]
+struct DbArgs {
+    #[command(subcommand)]
+    command: DbCommands,
+    /// SQLite database file
+    #[arg(long, default_value = "jsai.sqlite")]
+    db: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum DbCommands {
+    /// Write datasets into a new SQLite database, replacing an existing one
+    #[command(name = "export")]
+    Export {
+        /// Dataset files or directories of them
+        #[arg(num_args = 1.., required = true)]
+        datasets: Vec<String>,
+        /// CSV file of additional affiliation aliases with the header `alias,canonical,org_type`
+        #[arg(long)]
+        affiliation_aliases: Option<String>,
+    },
+    /// Run an SQL query on the database and print the result
+    #[command(name = "query")]
+    Query {
+        sql: String,
+        #[arg(short, long, value_enum, default_value_t = QueryFormat::Table)]
+        format: QueryFormat,
+    },
 }
 
 #[tokio::main]
@@ -54,59 +297,461 @@ async fn main() {
             println!("Crawling JSAI 2021 data...");
             if let Err(e) = crawl_jsai2021().await {
                 eprintln!("Error crawling JSAI 2021: {}", e);
+                std::process::exit(1);
             }
         }
         SubCommands::CrawlJsai2022 => {
             println!("Crawling JSAI 2022 data...");
             if let Err(e) = crawl_jsai2022().await {
                 eprintln!("Error crawling JSAI 2022: {}", e);
+                std::process::exit(1);
             }
         }
         SubCommands::CrawlJsai2023 => {
             println!("Crawling JSAI 2023 data...");
             if let Err(e) = crawl_jsai2023().await {
                 eprintln!("Error crawling JSAI 2023: {}", e);
+                std::process::exit(1);
             }
         }
         SubCommands::CrawlJsai2024 => {
             println!("Crawling JSAI 2024 data...");
             if let Err(e) = crawl_jsai2024().await {
                 eprintln!("Error crawling JSAI 2024: {}", e);
+                std::process::exit(1);
             }
         }
         SubCommands::CrawlJsai2025 => {
             if let Err(e) = crawl_jsai2025().await {
                 eprintln!("Error crawling JSAI 2025: {}", e);
+                std::process::exit(1);
             }
         }
         SubCommands::Analyze(args) => {
-            println!("Analyzing JSAI {} data from file: {}", args.year, args.data);
-
-            let mut sessions = Vec::new();
-            if args.year == 2021 {
-                sessions = crawlers::jsai2021::kernel::load_sessions_from_json(&args.data)
-                    .expect("Failed to load JSAI 2021 sessions");
-            } else if args.year == 2022 {
-                sessions = crawlers::jsai2022::kernel::load_sessions_from_json(&args.data)
-                    .expect("Failed to load JSAI 2022 sessions");
-            } else if args.year == 2023 {
-                sessions = crawlers::jsai2023::kernel::load_sessions_from_json(&args.data)
-                    .expect("Failed to load JSAI 2023 sessions");
-            } else if args.year == 2024 {
-                sessions = crawlers::jsai2024::kernel::load_sessions_from_json(&args.data)
-                    .expect("Failed to load JSAI 2024 sessions");
-            } else if args.year == 2025 {
-                sessions = crawlers::jsai2025::kernel::load_sessions_from_json(&args.data)
-                    .expect("Failed to load JSAI 2025 sessions");
+            let paths = args.data.iter().map(PathBuf::from).collect::<Vec<_>>();
+            let mut sessions_by_year = match load_sessions_by_year(&paths) {
+                Ok(sessions_by_year) => sessions_by_year,
+                Err(e) => {
+                    eprintln!("Error loading datasets: {}", e);
+                    return;
+                }
+            };
+            if let Some(year) = args.year {
+                sessions_by_year.retain(|y, _| *y == year);
+                if sessions_by_year.is_empty() {
+                    eprintln!("No sessions of {} in {}", year, args.data.join(", "));
+                    return;
+                }
             }
 
+            let affiliations = match &args.affiliation_aliases {
+                Some(path) => match AffiliationDictionary::bundled_with(&PathBuf::from(path)) {
+                    Ok(affiliations) => affiliations,
+                    Err(e) => {
+                        eprintln!("Error loading affiliation aliases {}: {}", path, e);
+                        return;
+                    }
+                },
+                None => AffiliationDictionary::bundled(),
+            };
+            let output_dir = PathBuf::from(args.output_dir.unwrap());
             let mut stats = Stats::default();
-            if let Err(e) =
-                stats.analyze(args.year, sessions, PathBuf::from(args.output_dir.unwrap()))
+            for (year, sessions) in sessions_by_year {
+                println!("Analyzing JSAI {} ({} sessions)", year, sessions.len());
+                if let Err(e) = stats.analyze(
+                    year,
+                    sessions,
+                    output_dir.clone(),
+                    &affiliations,
+                    args.affiliation_level,
+                ) {
+                    eprintln!("Error analyzing JSAI {}: {}", year, e);
+                    return;
+                }
+            }
+            println!("Analysis completed successfully.");
+        }
+        SubCommands::Cache(args) => {
+            let cache = LlmCache::new(&args.dir);
+            match args.command {
+                CacheCommands::Clear => match cache.clear() {
+                    Ok(count) => println!("Removed {} cached responses from {}", count, args.dir),
+                    Err(e) => eprintln!("Error clearing cache: {}", e),
+                },
+                CacheCommands::Export { output } => match cache.export(&output) {
+                    Ok(count) => println!("Exported {} cached responses to {}", count, output),
+                    Err(e) => eprintln!("Error exporting cache: {}", e),
+                },
+            }
+        }
+        SubCommands::ParseAuthors(args) => {
+            let prompt = match PromptTemplate::parse_authors_or_load(
+                args.prompt.as_ref().map(PathBuf::from).as_deref(),
+            ) {
+                Ok(prompt) => prompt,
+                Err(e) => {
+                    eprintln!("Error loading prompt: {}", e);
+                    return;
+                }
+            };
+            let backend = match LlmConfig::from_env().and_then(|config| create_backend(&config)) {
+                Ok(backend) => backend,
+                Err(e) => {
+                    eprintln!("Error creating LLM backend: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = parse_authors_in_dataset(
+                backend.as_ref(),
+                &prompt,
+                &PathBuf::from(&args.dataset),
+                args.concurrency,
+                args.force,
+            )
+            .await
             {
-                eprintln!("Error analyzing data: {}", e);
+                eprintln!("Error parsing authors: {}", e);
+            }
+            for line in backend.summary() {
+                println!("{}", line);
+            }
+        }
+        SubCommands::EvalAuthors(args) => {
+            let prompt = match PromptTemplate::parse_authors_or_load(
+                args.prompt.as_ref().map(PathBuf::from).as_deref(),
+            ) {
+                Ok(prompt) => prompt,
+                Err(e) => {
+                    eprintln!("Error loading prompt: {}", e);
+                    return;
+                }
+            };
+            let examples = match load_gold_examples(&PathBuf::from(&args.gold)) {
+                Ok(examples) => examples,
+                Err(e) => {
+                    eprintln!("Error loading gold file {}: {}", args.gold, e);
+                    return;
+                }
+            };
+            let backend = if args.parser == AuthorParserKind::Rules {
+                None
             } else {
-                println!("Analysis completed successfully.");
+                match LlmConfig::from_env().and_then(|config| create_backend(&config)) {
+                    Ok(backend) => Some(backend),
+                    Err(e) => {
+                        eprintln!("Error creating LLM backend: {}", e);
+                        return;
+                    }
+                }
+            };
+            let report =
+                match evaluate_author_parser(args.parser, backend.as_deref(), &prompt, &examples)
+                    .await
+                {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("Error evaluating author parser: {}", e);
+                        return;
+                    }
+                };
+
+            println!("Parser:               {:?}", args.parser);
+            if let Some(backend) = &backend {
+                println!("Model:                {}", backend.model_id());
+                println!("Prompt:               {}@{}", prompt.id, prompt.version);
+            }
+            println!("Examples:             {}", report.examples);
+            println!("Errors:               {}", report.errors);
+            println!("Exact match:          {:.3}", report.exact_match);
+            println!("Name precision:       {:.3}", report.name_precision);
+            println!("Name recall:          {:.3}", report.name_recall);
+            println!("Affiliation accuracy: {:.3}", report.affiliation_accuracy);
+            println!("Presenter accuracy:   {:.3}", report.presenter_accuracy);
+            if args.verbose {
+                for mismatch in &report.mismatches {
+                    println!("  {}", mismatch);
+                }
+            }
+            if let Some(backend) = &backend {
+                for line in backend.summary() {
+                    println!("{}", line);
+                }
+            }
+            if let Some(output) = args.output {
+                let written = serde_json::to_string_pretty(&report)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| Ok(std::fs::write(&output, json)?));
+                if let Err(e) = written {
+                    eprintln!("Error writing report to {}: {}", output, e);
+                }
+            }
+        }
+        SubCommands::ResolveAuthors(args) => {
+            let overrides = match &args.overrides {
+                Some(path) => match load_overrides(&PathBuf::from(path)) {
+                    Ok(overrides) => overrides,
+                    Err(e) => {
+                        eprintln!("Error loading overrides {}: {}", path, e);
+                        return;
+                    }
+                },
+                None => Vec::new(),
+            };
+            let mut datasets = Vec::new();
+            for path in &args.datasets {
+                match Dataset::load(&PathBuf::from(path)) {
+                    Ok(dataset) => datasets.push(dataset),
+                    Err(e) => {
+                        eprintln!("Error loading dataset {}: {}", path, e);
+                        return;
+                    }
+                }
+            }
+
+            for session in datasets.iter_mut().flat_map(|d| d.sessions.iter_mut()) {
+                fill_name_readings(&mut session.authors);
+            }
+            let identities = resolve_author_ids(&mut datasets, &overrides);
+            for (path, dataset) in args.datasets.iter().zip(&datasets) {
+                if let Err(e) = dataset.save(&PathBuf::from(path)) {
+                    eprintln!("Error saving dataset {}: {}", path, e);
+                }
+            }
+            let written = serde_json::to_string_pretty(&identities)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| Ok(std::fs::write(&args.output, json)?));
+            match written {
+                Ok(()) => println!(
+                    "Resolved {} authors, registry saved to {}",
+                    identities.len(),
+                    args.output
+                ),
+                Err(e) => eprintln!("Error writing registry to {}: {}", args.output, e),
+            }
+        }
+        SubCommands::LinkIds(args) => {
+            let researchers = match load_researchers(&PathBuf::from(&args.mapping)) {
+                Ok(researchers) => researchers,
+                Err(e) => {
+                    eprintln!("Error loading mapping {}: {}", args.mapping, e);
+                    return;
+                }
+            };
+            let mut datasets = Vec::new();
+            for path in &args.datasets {
+                match Dataset::load(&PathBuf::from(path)) {
+                    Ok(dataset) => datasets.push(dataset),
+                    Err(e) => {
+                        eprintln!("Error loading dataset {}: {}", path, e);
+                        return;
+                    }
+                }
+            }
+
+            let report = link_researcher_ids(&mut datasets, &researchers, args.min_similarity);
+            for (path, dataset) in args.datasets.iter().zip(&datasets) {
+                if let Err(e) = dataset.save(&PathBuf::from(path)) {
+                    eprintln!("Error saving dataset {}: {}", path, e);
+                }
+            }
+            for author in &report.ambiguous {
+                println!("Ambiguous: {}", author);
+            }
+            println!(
+                "Linked {} authors ({} already linked, {} ambiguous, {} unmatched)",
+                report.linked,
+                report.already_linked,
+                report.ambiguous.len(),
+                report.unmatched
+            );
+        }
+        SubCommands::Audit(args) => {
+            let dataset_path = PathBuf::from(&args.dataset);
+            let verdicts_path = args
+                .verdicts
+                .map(PathBuf::from)
+                .unwrap_or_else(|| verdicts_path(&dataset_path));
+            let summary = if args.report {
+                load_audit_records(&verdicts_path).map(|records| {
+                    let records = records
+                        .into_iter()
+                        .filter(|r| r.seed == args.seed)
+                        .collect::<Vec<_>>();
+                    summarize_audit(&records)
+                })
+            } else {
+                let reviewer = args
+                    .reviewer
+                    .or_else(|| std::env::var("USER").ok())
+                    .unwrap_or_default();
+                run_audit(
+                    &dataset_path,
+                    &verdicts_path,
+                    args.sample,
+                    args.seed,
+                    &reviewer,
+                )
+            };
+            match summary {
+                Ok(AuditSummary {
+                    reviewed,
+                    incorrect,
+                    error_rate,
+                    error_rate_low,
+                    error_rate_high,
+                }) => println!(
+                    "Reviewed {} sessions (seed {}), {} incorrect: error rate {:.1}% (95% CI {:.1}%-{:.1}%)",
+                    reviewed,
+                    args.seed,
+                    incorrect,
+                    error_rate * 100.0,
+                    error_rate_low * 100.0,
+                    error_rate_high * 100.0
+                ),
+                Err(e) => eprintln!("Error auditing {}: {}", args.dataset, e),
+            }
+        }
+        SubCommands::Db(args) => match args.command {
+            DbCommands::Export {
+                datasets,
+                affiliation_aliases,
+            } => {
+                let affiliations = match &affiliation_aliases {
+                    Some(path) => match AffiliationDictionary::bundled_with(&PathBuf::from(path)) {
+                        Ok(affiliations) => affiliations,
+                        Err(e) => {
+                            eprintln!("Error loading affiliation aliases {}: {}", path, e);
+                            return;
+                        }
+                    },
+                    None => AffiliationDictionary::bundled(),
+                };
+                let paths = datasets.iter().map(PathBuf::from).collect::<Vec<_>>();
+                match export_database(&paths, &PathBuf::from(&args.db), &affiliations) {
+                    Ok(report) => println!(
+                        "Exported {} sessions, {} sections, {} authors and {} affiliations to {} ({} duplicated sessions skipped)",
+                        report.sessions,
+                        report.sections,
+                        report.authors,
+                        report.affiliations,
+                        args.db,
+                        report.duplicates
+                    ),
+                    Err(e) => eprintln!("Error exporting to {}: {}", args.db, e),
+                }
+            }
+            DbCommands::Query { sql, format } => {
+                if let Err(e) = query_database(&PathBuf::from(&args.db), &sql, format) {
+                    eprintln!("Error running query: {}", e);
+                }
+            }
+        },
+        SubCommands::Export(args) => {
+            let paths = args.datasets.iter().map(PathBuf::from).collect::<Vec<_>>();
+            let sessions = match load_sessions(&paths) {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    eprintln!("Error loading datasets: {}", e);
+                    return;
+                }
+            };
+            match export_sessions(
+                &sessions,
+                &args.tables,
+                &args.columns,
+                args.format,
+                &PathBuf::from(&args.output_dir),
+            ) {
+                Ok(written) => {
+                    for (file_path, rows) in written {
+                        println!("Wrote {} rows to {}", rows, file_path.display());
+                    }
+                }
+                Err(e) => eprintln!("Error exporting sessions: {}", e),
+            }
+        }
+        SubCommands::Cite(args) => {
+            let paths = args.datasets.iter().map(PathBuf::from).collect::<Vec<_>>();
+            let sessions = match load_sessions(&paths) {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    eprintln!("Error loading datasets: {}", e);
+                    return;
+                }
+            };
+            let selected = select_sessions(&sessions, &args.ids, args.query.as_deref());
+            if selected.is_empty() {
+                eprintln!("No sessions match the given ids or query");
+                return;
+            }
+            let bibliography = match format_bibliography(&selected, args.format) {
+                Ok(bibliography) => bibliography,
+                Err(e) => {
+                    eprintln!("Error formatting bibliography: {}", e);
+                    return;
+                }
+            };
+            match &args.output {
+                Some(output) => match std::fs::write(output, bibliography) {
+                    Ok(()) => println!("Wrote {} entries to {}", selected.len(), output),
+                    Err(e) => eprintln!("Error writing {}: {}", output, e),
+                },
+                None => print!("{}", bibliography),
+            }
+        }
+        SubCommands::Validate(args) => {
+            let dataset = match Dataset::load(&PathBuf::from(&args.dataset)) {
+                Ok(dataset) => dataset,
+                Err(e) => {
+                    eprintln!("Error loading {}: {}", args.dataset, e);
+                    std::process::exit(1);
+                }
+            };
+            let thresholds = args.threshold.into_iter().collect();
+            let results = lint_dataset(&dataset, &thresholds);
+            for result in &results {
+                println!(
+                    "[{}] {}: {}/{} ({:.1}%, threshold {:.1}%)",
+                    if result.failed() { "FAIL" } else { "ok" },
+                    result.check.label(),
+                    result.count,
+                    result.total,
+                    result.ratio() * 100.0,
+                    result.threshold * 100.0
+                );
+                for example in &result.examples {
+                    println!("    {}", example);
+                }
+            }
+            let failed = results.iter().filter(|r| r.failed()).count();
+            if failed > 0 {
+                eprintln!(
+                    "{} of {} checks failed for {}",
+                    failed,
+                    results.len(),
+                    args.dataset
+                );
+                std::process::exit(1);
+            }
+            println!("All checks passed for {}", args.dataset);
+        }
+        SubCommands::SampleGold(args) => {
+            let paths = args.datasets.iter().map(PathBuf::from).collect::<Vec<_>>();
+            let sessions = match load_sessions(&paths) {
+                Ok(sessions) => sessions,
+                Err(e) => {
+                    eprintln!("Error loading datasets: {}", e);
+                    return;
+                }
+            };
+            let candidates = sample_gold_candidates(&sessions, args.size, args.seed);
+            match write_gold_examples(&PathBuf::from(&args.output), &candidates) {
+                Ok(()) => println!(
+                    "Wrote {} candidates to {}; correct their labels, then append them to eval/gold_authors.jsonl",
+                    candidates.len(),
+                    args.output
+                ),
+                Err(e) => eprintln!("Error writing {}: {}", args.output, e),
             }
         }
     }
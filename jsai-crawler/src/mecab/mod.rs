@@ -9,13 +9,19 @@ use vibrato::{Dictionary, Tokenizer};
 const MECAB_DIC: &'static str = "unidic-cwj-3_1_1+compact-dual/system.dic.zst";
 const MECAB_USER_DIC: &'static str = include_str!("dic/user_dic.csv");
 const STOPWORDS: &'static str = include_str!("dic/stopwords.csv");
+/// Feature fields of UniDic holding the reading of the surface form and of the lemma.
+const UNIDIC_KANA_FIELD: usize = 20;
+const UNIDIC_LFORM_FIELD: usize = 6;
 
 #[derive(Debug, Clone)]
 pub struct MeCabToken {
     pub surface: String,
     pub pos1: String,
     pub pos2: String,
+    pub pos4: String,
     pub feature: String,
+    /// Reading in katakana, empty when the dictionary has none (e.g. user dictionary entries)
+    pub reading: String,
 }
 
 static MECAB_TOKENIZER: Lazy<Tokenizer> = Lazy::new(|| get_tokenizer());
@@ -126,12 +132,24 @@ pub fn mecab_tokenize(text: &str) -> Vec<MeCabToken> {
             surface: t.surface().to_string(),
             pos1: features.get(0).unwrap_or(&"").to_string(),
             pos2: features.get(1).unwrap_or(&"").to_string(),
+            pos4: features.get(3).unwrap_or(&"").to_string(),
             feature: features.get(2).unwrap_or(&"").to_string(),
+            reading: feature_reading(&features),
         });
     }
     return tokens;
 }
 
+/// Reading of a token from its UniDic features: the kana field, or the lemma reading when the
+/// kana field is unknown (`*`) or missing.
+fn feature_reading(features: &[&str]) -> String {
+    let known = |field: usize| features.get(field).filter(|r| !r.is_empty() && **r != "*");
+    known(UNIDIC_KANA_FIELD)
+        .or_else(|| known(UNIDIC_LFORM_FIELD))
+        .unwrap_or(&"")
+        .to_string()
+}
+
 pub fn add_word_to_user_dic(word: &str) {
     let mut f = csv::Reader::from_reader(std::io::Cursor::new(MECAB_USER_DIC));
     let lines = f
@@ -194,3 +212,266 @@ pub fn generate_wordcloud_input(text: &str) -> String {
 
     return wordcloud_input;
 }
+
+fn hiragana_to_katakana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ぁ'..='ゖ' => char::from_u32(c as u32 + 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Reading of a Japanese name in katakana, e.g. `ヤマダ タロウ` for `山田 太郎`.
+///
+/// The family and given names are separated by a space, taken from the name or from the
+/// 姓/名 tags of UniDic. Returns `None` when a part of the name has no reading.
+pub fn name_reading(name: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for part in name.split_whitespace() {
+        let mut reading = String::new();
+        let mut previous_pos4 = String::new();
+        for token in mecab_tokenize(part) {
+            let token_reading = if token.reading.is_empty() {
+                // kana is its own reading
+                let kana = hiragana_to_katakana(&token.surface);
+                if !kana.chars().all(|c| matches!(c, 'ァ'..='ヺ' | 'ー')) {
+                    return None;
+                }
+                kana
+            } else {
+                token.reading.clone()
+            };
+            if previous_pos4 == "姓" && token.pos4 == "名" {
+                reading.push(' ');
+            }
+            reading.push_str(&token_reading);
+            previous_pos4 = token.pos4;
+        }
+        if reading.is_empty() {
+            return None;
+        }
+        parts.push(reading);
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join(" "))
+}
+
+const KANA_DIGRAPHS: &[(&str, &str)] = &[
+    ("キャ", "kya"),
+    ("キュ", "kyu"),
+    ("キョ", "kyo"),
+    ("シャ", "sha"),
+    ("シュ", "shu"),
+    ("ショ", "sho"),
+    ("シェ", "she"),
+    ("チャ", "cha"),
+    ("チュ", "chu"),
+    ("チョ", "cho"),
+    ("チェ", "che"),
+    ("ニャ", "nya"),
+    ("ニュ", "nyu"),
+    ("ニョ", "nyo"),
+    ("ヒャ", "hya"),
+    ("ヒュ", "hyu"),
+    ("ヒョ", "hyo"),
+    ("ミャ", "mya"),
+    ("ミュ", "myu"),
+    ("ミョ", "myo"),
+    ("リャ", "rya"),
+    ("リュ", "ryu"),
+    ("リョ", "ryo"),
+    ("ギャ", "gya"),
+    ("ギュ", "gyu"),
+    ("ギョ", "gyo"),
+    ("ジャ", "ja"),
+    ("ジュ", "ju"),
+    ("ジョ", "jo"),
+    ("ジェ", "je"),
+    ("ヂャ", "ja"),
+    ("ヂュ", "ju"),
+    ("ヂョ", "jo"),
+    ("ビャ", "bya"),
+    ("ビュ", "byu"),
+    ("ビョ", "byo"),
+    ("ピャ", "pya"),
+    ("ピュ", "pyu"),
+    ("ピョ", "pyo"),
+    ("ファ", "fa"),
+    ("フィ", "fi"),
+    ("フェ", "fe"),
+    ("フォ", "fo"),
+    ("ティ", "ti"),
+    ("ディ", "di"),
+    ("ウィ", "wi"),
+    ("ウェ", "we"),
+    ("ウォ", "wo"),
+];
+
+const KANA: &[(char, &str)] = &[
+    ('ア', "a"),
+    ('イ', "i"),
+    ('ウ', "u"),
+    ('エ', "e"),
+    ('オ', "o"),
+    ('カ', "ka"),
+    ('キ', "ki"),
+    ('ク', "ku"),
+    ('ケ', "ke"),
+    ('コ', "ko"),
+    ('サ', "sa"),
+    ('シ', "shi"),
+    ('ス', "su"),
+    ('セ', "se"),
+    ('ソ', "so"),
+    ('タ', "ta"),
+    ('チ', "chi"),
+    ('ツ', "tsu"),
+    ('テ', "te"),
+    ('ト', "to"),
+    ('ナ', "na"),
+    ('ニ', "ni"),
+    ('ヌ', "nu"),
+    ('ネ', "ne"),
+    ('ノ', "no"),
+    ('ハ', "ha"),
+    ('ヒ', "hi"),
+    ('フ', "fu"),
+    ('ヘ', "he"),
+    ('ホ', "ho"),
+    ('マ', "ma"),
+    ('ミ', "mi"),
+    ('ム', "mu"),
+    ('メ', "me"),
+    ('モ', "mo"),
+    ('ヤ', "ya"),
+    ('ユ', "yu"),
+    ('ヨ', "yo"),
+    ('ラ', "ra"),
+    ('リ', "ri"),
+    ('ル', "ru"),
+    ('レ', "re"),
+    ('ロ', "ro"),
+    ('ワ', "wa"),
+    ('ヰ', "i"),
+    ('ヱ', "e"),
+    ('ヲ', "o"),
+    ('ン', "n"),
+    ('ガ', "ga"),
+    ('ギ', "gi"),
+    ('グ', "gu"),
+    ('ゲ', "ge"),
+    ('ゴ', "go"),
+    ('ザ', "za"),
+    ('ジ', "ji"),
+    ('ズ', "zu"),
+    ('ゼ', "ze"),
+    ('ゾ', "zo"),
+    ('ダ', "da"),
+    ('ヂ', "ji"),
+    ('ヅ', "zu"),
+    ('デ', "de"),
+    ('ド', "do"),
+    ('バ', "ba"),
+    ('ビ', "bi"),
+    ('ブ', "bu"),
+    ('ベ', "be"),
+    ('ボ', "bo"),
+    ('パ', "pa"),
+    ('ピ', "pi"),
+    ('プ', "pu"),
+    ('ペ', "pe"),
+    ('ポ', "po"),
+    ('ヴ', "vu"),
+    ('ァ', "a"),
+    ('ィ', "i"),
+    ('ゥ', "u"),
+    ('ェ', "e"),
+    ('ォ', "o"),
+    ('ャ', "ya"),
+    ('ュ', "yu"),
+    ('ョ', "yo"),
+];
+
+/// Romanize katakana with the Hepburn system as used in passports: long vowels are not marked
+/// (`サトウ` -> `sato`, `ユウキ` -> `yuki`) and `ン` becomes `m` before `b`, `m` and `p`.
+pub fn romanize(kana: &str) -> String {
+    let chars = hiragana_to_katakana(kana).chars().collect::<Vec<char>>();
+    let mut romaji = String::new();
+    let mut double_next = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let digraph = chars
+            .get(i..i + 2)
+            .map(|pair| pair.iter().collect::<String>())
+            .and_then(|pair| KANA_DIGRAPHS.iter().find(|(k, _)| *k == pair));
+        let (syllable, len) = match (digraph, KANA.iter().find(|(k, _)| *k == c)) {
+            (Some((_, r)), _) => (r.to_string(), 2),
+            (None, Some((_, r))) => (r.to_string(), 1),
+            (None, None) => {
+                match c {
+                    'ッ' => double_next = true,
+                    'ー' => {}
+                    _ => romaji.push(c),
+                }
+                i += 1;
+                continue;
+            }
+        };
+        i += len;
+
+        // long vowels: おう, おお and うう are written as a single vowel
+        if (syllable == "u" && (romaji.ends_with('o') || romaji.ends_with('u')))
+            || (syllable == "o" && romaji.ends_with('o'))
+        {
+            continue;
+        }
+        if romaji.ends_with('n') && syllable.starts_with(['b', 'm', 'p']) {
+            romaji.pop();
+            romaji.push('m');
+        }
+        if double_next {
+            let first = if syllable.starts_with("ch") {
+                't'
+            } else {
+                syllable.chars().next().unwrap()
+            };
+            romaji.push(first);
+            double_next = false;
+        }
+        romaji.push_str(&syllable);
+    }
+    romaji
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UniDic features with `lform` in the lemma reading field and `kana` in the kana field.
+    fn features(lform: &'static str, kana: Option<&'static str>) -> Vec<&'static str> {
+        let mut features = vec!["名詞", "固有名詞", "人名", "姓", "*", "*", lform];
+        features.resize(UNIDIC_KANA_FIELD, "*");
+        features.extend(kana);
+        features
+    }
+
+    #[test]
+    fn reading_prefers_the_kana_field() {
+        assert_eq!(
+            feature_reading(&features("ヤマダ", Some("ヤマダ"))),
+            "ヤマダ"
+        );
+    }
+
+    #[test]
+    fn unknown_kana_falls_back_to_the_lemma_reading() {
+        assert_eq!(feature_reading(&features("ヤマダ", Some("*"))), "ヤマダ");
+        assert_eq!(feature_reading(&features("ヤマダ", None)), "ヤマダ");
+        assert_eq!(feature_reading(&features("*", Some("*"))), "");
+        assert_eq!(feature_reading(&["名詞"]), "");
+    }
+}
@@ -0,0 +1,116 @@
+use std::error::Error;
+use std::time::{Duration, Instant};
+use thirtyfour::prelude::*;
+use url::Url;
+
+const NEXT_BUTTON_SELECTOR: &str = "#pageNavHead li:last-child a";
+const ARTICLE_SELECTOR: &str = "section article";
+pub const PAGE_LOAD_TIMEOUT: Duration = Duration::from_secs(20);
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+fn page_number(url: &Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == "page")
+        .map(|(_, value)| value.to_string())
+}
+
+/// Times the "次へ" button is clicked before the page is given up.
+const NEXT_PAGE_ATTEMPTS: usize = 2;
+
+/// Wait until the `page` query parameter of the current URL differs from `previous`.
+///
+/// Returns `Ok(false)` when it does not change within `PAGE_LOAD_TIMEOUT`.
+async fn wait_for_page_change(
+    driver: &WebDriver,
+    previous: &Option<String>,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let started = Instant::now();
+    loop {
+        let current = page_number(&driver.current_url().await?);
+        if current != *previous {
+            return Ok(true);
+        }
+        if started.elapsed() > PAGE_LOAD_TIMEOUT {
+            return Ok(false);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn find_next_button(
+    driver: &WebDriver,
+) -> Result<Option<WebElement>, Box<dyn Error + Send + Sync>> {
+    let next_button = match driver
+        .query(By::Css(NEXT_BUTTON_SELECTOR))
+        .nowait()
+        .first_opt()
+        .await?
+    {
+        Some(button) => button,
+        None => return Ok(None), // no next button found
+    };
+    if !next_button.text().await?.contains("次へ") {
+        return Ok(None); // no more pages
+    }
+    Ok(Some(next_button))
+}
+
+/// Click the "次へ" button of the Confit pagination bar and wait until the next page is loaded.
+///
+/// Returns `Ok(false)` when the current page is the last one. When the next page does not load
+/// after `NEXT_PAGE_ATTEMPTS` clicks, an error is returned; callers should keep what they have
+/// collected so far.
+pub async fn goto_next_page(driver: &WebDriver) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let previous_page = page_number(&driver.current_url().await?);
+    let old_article = driver
+        .query(By::Css(ARTICLE_SELECTOR))
+        .nowait()
+        .first_opt()
+        .await?;
+
+    for _ in 0..NEXT_PAGE_ATTEMPTS {
+        let next_button = match find_next_button(driver).await? {
+            Some(button) => button,
+            None => return Ok(false),
+        };
+        driver
+            .action_chain()
+            .click_element(&next_button)
+            .perform()
+            .await?;
+
+        // Confit sometimes swaps the list without updating the URL, so a detached list also counts
+        let changed = wait_for_page_change(driver, &previous_page).await?;
+        let unloaded = match &old_article {
+            Some(old_article) => !old_article.is_present().await?,
+            None => true,
+        };
+        if !changed && !unloaded {
+            continue;
+        }
+
+        // the old list must be detached before the new one is read, otherwise we collect the old page again
+        if let Some(old_article) = &old_article
+            && !unloaded
+        {
+            old_article
+                .wait_until()
+                .wait(PAGE_LOAD_TIMEOUT, POLL_INTERVAL)
+                .error("Timed out waiting for the previous page to unload")
+                .stale()
+                .await?;
+        }
+        driver
+            .query(By::Css(ARTICLE_SELECTOR))
+            .wait(PAGE_LOAD_TIMEOUT, POLL_INTERVAL)
+            .first()
+            .await?;
+        return Ok(true);
+    }
+
+    Err(format!(
+        "The page after page {:?} did not load after {} attempts",
+        previous_page, NEXT_PAGE_ATTEMPTS
+    )
+    .into())
+}
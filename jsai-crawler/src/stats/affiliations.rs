@@ -0,0 +1,599 @@
+use crate::stats::authors::{SEPARATORS, normalize_for_match};
+use crate::stats::models::Session;
+use anyhow::Result;
+use clap::ValueEnum;
+use fxhash::{FxHashMap, FxHashSet};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+const AFFILIATION_ALIASES: &str = include_str!("dic/affiliation_aliases.csv");
+
+/// Legal forms that are not part of the organization name.
+const LEGAL_FORMS: &[&str] = &[
+    "国立大学法人",
+    "公立大学法人",
+    "学校法人",
+    "大学共同利用機関法人",
+    "国立研究開発法人",
+    "独立行政法人",
+    "一般社団法人",
+    "公益財団法人",
+    "一般財団法人",
+    "株式会社",
+    "有限会社",
+    "合同会社",
+    "(株)",
+    "(有)",
+];
+const LEGAL_SUFFIXES: &[&str] = &[
+    "Co., Ltd.",
+    "Co.,Ltd.",
+    "Co. Ltd.",
+    "Corporation",
+    "Corp.",
+    "Inc.",
+    "Ltd.",
+    "Limited",
+    "LLC",
+    "K.K.",
+    "GmbH",
+];
+
+static UNIVERSITY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<university>.+?(?:大学院大学|大学))").unwrap());
+static COMPANY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)株式会社|有限会社|合同会社|\(株\)|\(有\)|\b(?:inc|corp|corporation|co|ltd|limited|llc|k\.k|gmbh)\b\.?").unwrap()
+});
+static UNIVERSITY_TYPE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)大学|高等専門学校|高専|universit|\bcollege\b").unwrap());
+/// A sub-unit of a Japanese affiliation ends with one of these, e.g. `情報理工学系研究科` or `知能機械情報学専攻`.
+static UNIT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?P<unit>.+?(?:研究室|ラボ|研究科|学府|学部|学環|学院|専攻|学科|研究院|研究所|センター|機構|部門|本部|事業部|研究部|グループ|講座|コース|プログラム|大学院))")
+        .unwrap()
+});
+/// Start of the text after an organization name known to the dictionary, where a sub-unit begins,
+/// e.g. `研究開発グループ` after `日立製作所`. Other text means the name only looks like a known one,
+/// as `東大寺` does like `東大`.
+static UNIT_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:\s|大学院|研究|学部|学科|学府|学環|学院|専攻|センター|機構|部門|本部|事業部|グループ|ラボ|講座|コース|プログラム|総合研究|技術研究|中央研究|基礎研究)")
+        .unwrap()
+});
+static LAB_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(?:研究室|ラボ|\blab\b|\blab\.|laboratory)$").unwrap());
+static ORGANIZATION_TYPE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)universit|\bcollege\b|\binstitute of technology\b|\binc\b|\bcorp|\bltd\b|\bco\.|\briken\b|national institute")
+        .unwrap()
+});
+/// An English sub-unit, which is listed next to its organization after a comma, e.g.
+/// `Graduate School of Information Science and Technology, The University of Tokyo`.
+static ENGLISH_UNIT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:graduate school|school of|faculty of|department of|dept\.? of|division of|center for|centre for|laboratory|lab\b|course|program)|(?:laboratory|\blab\.?)$")
+        .unwrap()
+});
+static NATIONAL_INSTITUTE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)国立|研究開発法人|独立行政法人|研究所|研究機構|\bnational institute\b|\bagency\b",
+    )
+    .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrgType {
+    University,
+    Company,
+    NationalInstitute,
+    #[default]
+    Other,
+}
+
+impl OrgType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrgType::University => "University",
+            OrgType::Company => "Company",
+            OrgType::NationalInstitute => "National Institute",
+            OrgType::Other => "Other",
+        }
+    }
+}
+
+/// A canonical organization an affiliation string refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Organization {
+    pub name: String,
+    pub org_type: OrgType,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AliasRecord {
+    alias: String,
+    canonical: String,
+    #[serde(default)]
+    org_type: OrgType,
+}
+
+/// Alias dictionary mapping spellings, abbreviations and English names of an organization to
+/// its canonical name and type. The bundled dictionary is `src/stats/dic/affiliation_aliases.csv`.
+#[derive(Debug, Clone, Default)]
+pub struct AffiliationDictionary {
+    aliases: FxHashMap<String, Organization>,
+}
+
+impl AffiliationDictionary {
+    pub fn bundled() -> Self {
+        let mut dictionary = Self::default();
+        dictionary
+            .extend_from_reader(AFFILIATION_ALIASES.as_bytes())
+            .expect("Invalid bundled affiliation dictionary");
+        dictionary
+    }
+
+    /// The bundled dictionary extended with the CSV file at `file_path`, whose entries take precedence.
+    pub fn bundled_with(file_path: &Path) -> Result<Self> {
+        let mut dictionary = Self::bundled();
+        dictionary.extend_from_reader(std::fs::File::open(file_path)?)?;
+        Ok(dictionary)
+    }
+
+    /// Add the entries of a CSV with the header `alias,canonical,org_type`.
+    pub fn extend_from_reader<R: Read>(&mut self, reader: R) -> Result<()> {
+        let mut reader = csv::Reader::from_reader(reader);
+        for record in reader.deserialize() {
+            let record: AliasRecord = record?;
+            let organization = Organization {
+                name: record.canonical,
+                org_type: record.org_type,
+            };
+            self.aliases
+                .insert(normalize_for_match(&record.alias), organization.clone());
+            self.aliases
+                .insert(normalize_for_match(&organization.name), organization);
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Option<Organization> {
+        self.aliases.get(&normalize_for_match(name)).cloned()
+    }
+
+    /// Canonicalize one affiliation, e.g. `東京大学大学院情報理工学系研究科` or
+    /// `The University of Tokyo` to `東京大学`.
+    ///
+    /// The dictionary is tried first; otherwise legal forms are removed, a Japanese university name
+    /// is cut after `大学`, and the type is guessed from keywords.
+    pub fn canonicalize(&self, affiliation: &str) -> Option<Organization> {
+        let affiliation: String = affiliation.nfkc().collect();
+        let affiliation = affiliation
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if affiliation.is_empty() {
+            return None;
+        }
+        if let Some(organization) = self.lookup(&affiliation) {
+            return Some(organization);
+        }
+
+        let name = strip_legal_forms(&affiliation);
+        let name = match UNIVERSITY_REGEX.captures(&name) {
+            Some(caps) => caps["university"].to_string(),
+            None => name,
+        };
+        if let Some(organization) = self.lookup(&name) {
+            return Some(organization);
+        }
+        // a known organization followed or preceded by its sub-units, e.g. `日立製作所 研究開発グループ`
+        if let Some(organization) = name.split(' ').rev().find_map(|token| self.lookup(token)) {
+            return Some(organization);
+        }
+        Some(Organization {
+            org_type: guess_org_type(&affiliation, &name),
+            name,
+        })
+    }
+
+    /// Canonical organizations of an affiliation string, which may list several affiliations.
+    pub fn organizations(&self, affiliation: &str) -> Vec<Organization> {
+        let mut organizations = Vec::new();
+        for part in split_affiliations(affiliation) {
+            if let Some(organization) = self
+                .parse_hierarchy(&part)
+                .map(|hierarchy| hierarchy.organization)
+                && !organizations.contains(&organization)
+            {
+                organizations.push(organization);
+            }
+        }
+        organizations
+    }
+}
+
+/// Level at which affiliations are aggregated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AffiliationLevel {
+    /// The canonical organization, e.g. `東京大学`
+    #[default]
+    Organization,
+    /// The organization with its sub-units, e.g. `東京大学 / 大学院情報理工学系研究科`
+    Unit,
+    /// The organization, its sub-units and the lab
+    Lab,
+}
+
+/// An affiliation split into the organization, its sub-units (graduate school, department, division, ...)
+/// from the largest to the smallest, and the lab.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffiliationHierarchy {
+    pub raw: String,
+    pub organization: Organization,
+    pub units: Vec<String>,
+    pub lab: Option<String>,
+}
+
+impl AffiliationHierarchy {
+    /// Key to aggregate this affiliation at `level`, the parts joined by ` / `.
+    /// `None` when the affiliation has nothing at that level, e.g. no lab.
+    pub fn key(&self, level: AffiliationLevel) -> Option<String> {
+        let mut parts = vec![self.organization.name.clone()];
+        match level {
+            AffiliationLevel::Organization => {}
+            AffiliationLevel::Unit => {
+                if self.units.is_empty() {
+                    return None;
+                }
+                parts.extend(self.units.iter().cloned());
+            }
+            AffiliationLevel::Lab => {
+                parts.extend(self.units.iter().cloned());
+                parts.push(self.lab.clone()?);
+            }
+        }
+        Some(parts.join(" / "))
+    }
+}
+
+impl AffiliationDictionary {
+    /// Split one affiliation into organization, sub-units and lab, e.g.
+    /// `東京大学大学院情報理工学系研究科 知能機械情報学専攻 山田研究室` or
+    /// `Graduate School of Information Science and Technology, The University of Tokyo`.
+    pub fn parse_hierarchy(&self, affiliation: &str) -> Option<AffiliationHierarchy> {
+        let normalized: String = affiliation.nfkc().collect();
+        let normalized = strip_legal_forms(&normalized);
+        if normalized.is_empty() {
+            return None;
+        }
+
+        let (organization, mut units) = if normalized.contains(',') {
+            // English notation lists the units before or after the organization, separated by commas
+            let parts = normalized
+                .split(',')
+                .map(|part| part.trim().to_string())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<String>>();
+            let position = parts
+                .iter()
+                .position(|part| self.lookup(part).is_some())
+                .or_else(|| {
+                    parts
+                        .iter()
+                        .position(|part| ORGANIZATION_TYPE_REGEX.is_match(part))
+                })
+                .unwrap_or(0);
+            let mut units = parts[position + 1..].to_vec();
+            units.extend(parts[..position].iter().rev().cloned());
+            (parts[position].clone(), units)
+        } else {
+            let (organization, rest) = self.split_organization(&normalized);
+            let units = rest.split_whitespace().flat_map(split_units).collect();
+            (organization.to_string(), units)
+        };
+
+        let mut organization = self.canonicalize(&organization)?;
+        if organization.org_type == OrgType::Other {
+            // keywords of the whole affiliation, e.g. `株式会社` removed from the organization name
+            organization.org_type = guess_org_type(affiliation, &normalized);
+        }
+        let lab = match units.last() {
+            Some(unit) if LAB_REGEX.is_match(unit) => units.pop(),
+            _ => None,
+        };
+        Some(AffiliationHierarchy {
+            raw: affiliation.to_string(),
+            organization,
+            units,
+            lab,
+        })
+    }
+
+    /// Split a Japanese affiliation into the organization name and the rest.
+    fn split_organization<'a>(&self, affiliation: &'a str) -> (&'a str, &'a str) {
+        if let Some(caps) = UNIVERSITY_REGEX.captures(affiliation) {
+            return affiliation.split_at(caps["university"].len());
+        }
+        // the longest prefix known to the dictionary, e.g. `日立製作所` of `日立製作所研究開発グループ`
+        let known_prefix = affiliation
+            .char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .rev()
+            .filter(|end| {
+                *end == affiliation.len() || UNIT_BOUNDARY_REGEX.is_match(&affiliation[*end..])
+            })
+            .find(|end| self.lookup(&affiliation[..*end]).is_some());
+        if let Some(end) = known_prefix {
+            return affiliation.split_at(end);
+        }
+        // a known organization after its parent, e.g. `情報・システム研究機構 国立情報学研究所`
+        let mut start = 0;
+        for token in affiliation.split(' ') {
+            if self.lookup(token).is_some() {
+                return (token, &affiliation[start + token.len()..]);
+            }
+            start += token.len() + 1;
+        }
+        if affiliation.is_ascii() {
+            return (affiliation, "");
+        }
+        affiliation.split_once(' ').unwrap_or((affiliation, ""))
+    }
+
+    /// Organizations of an affiliation string aggregated at `level`, named by [`AffiliationHierarchy::key`].
+    pub fn organizations_at(
+        &self,
+        affiliation: &str,
+        level: AffiliationLevel,
+    ) -> Vec<Organization> {
+        if level == AffiliationLevel::Organization {
+            return self.organizations(affiliation);
+        }
+        self.parse_hierarchy(affiliation)
+            .and_then(|hierarchy| {
+                hierarchy.key(level).map(|name| Organization {
+                    name,
+                    org_type: hierarchy.organization.org_type,
+                })
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Split a Japanese unit string such as `大学院情報理工学系研究科知能機械情報学専攻` at unit suffixes;
+/// `大学院` is kept together with the graduate school that follows.
+fn split_units(text: &str) -> Vec<String> {
+    let mut units: Vec<String> = Vec::new();
+    let mut end = 0;
+    for caps in UNIT_REGEX.captures_iter(text) {
+        let unit = &caps["unit"];
+        match units.last_mut() {
+            Some(last) if *last == "大学院" => last.push_str(unit),
+            _ => units.push(unit.to_string()),
+        }
+        end = caps.get(0).unwrap().end();
+    }
+    if end < text.len() {
+        units.push(text[end..].to_string());
+    }
+    units
+}
+
+/// Split an affiliation on separators, keeping `Co., Ltd.` style legal suffixes with their company
+/// and English sub-units with the organization after them (or before them, at the end).
+fn split_affiliations(affiliation: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    // whether the last part is a sub-unit still waiting for its organization
+    let mut open_unit = false;
+    for part in affiliation.split(SEPARATORS) {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let is_unit = ENGLISH_UNIT_REGEX.is_match(part);
+        match parts.last_mut() {
+            Some(last) if LEGAL_SUFFIXES.contains(&part) || open_unit => {
+                last.push_str(", ");
+                last.push_str(part);
+                open_unit = is_unit;
+            }
+            _ => {
+                parts.push(part.to_string());
+                open_unit = is_unit;
+            }
+        }
+    }
+    // a trailing sub-unit belongs to the organization before it
+    if open_unit && parts.len() > 1 {
+        let unit = parts.pop().unwrap();
+        let last = parts.last_mut().unwrap();
+        last.push_str(", ");
+        last.push_str(&unit);
+    }
+    parts
+}
+
+fn strip_legal_forms(affiliation: &str) -> String {
+    let mut name = affiliation.to_string();
+    for form in LEGAL_FORMS {
+        name = name.replace(form, " ");
+    }
+    let mut name = name.trim().to_string();
+    loop {
+        let stripped = LEGAL_SUFFIXES
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+            .map(|rest| rest.trim_end_matches(|c: char| c == ',' || c.is_whitespace()));
+        match stripped {
+            Some(rest) if !rest.is_empty() => name = rest.to_string(),
+            _ => break,
+        }
+    }
+    name.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Guess the type from keywords; legal forms are only looked at for companies, since e.g.
+/// `大学共同利用機関法人` also precedes national institutes.
+fn guess_org_type(affiliation: &str, name: &str) -> OrgType {
+    if COMPANY_REGEX.is_match(affiliation) {
+        OrgType::Company
+    } else if UNIVERSITY_TYPE_REGEX.is_match(name) {
+        OrgType::University
+    } else if NATIONAL_INSTITUTE_REGEX.is_match(affiliation) {
+        OrgType::NationalInstitute
+    } else {
+        OrgType::Other
+    }
+}
+
+/// Count the sessions of each canonical organization, most frequent first.
+///
+/// With `presenters_only`, only the affiliations of the presenting authors are counted.
+pub fn count_organizations(
+    dictionary: &AffiliationDictionary,
+    sessions: &[Session],
+    presenters_only: bool,
+    level: AffiliationLevel,
+) -> Vec<(Organization, Vec<String>)> {
+    let mut counts: FxHashMap<Organization, FxHashSet<String>> = FxHashMap::default();
+    for session in sessions {
+        let authors = session
+            .authors
+            .iter()
+            .filter(|author| !presenters_only || author.is_presenter);
+        for author in authors {
+            let organizations = author
+                .affiliations
+                .iter()
+                .flat_map(|affiliation| dictionary.organizations_at(&affiliation.name, level));
+            for organization in organizations {
+                counts
+                    .entry(organization)
+                    .or_default()
+                    .insert(session.title_with_id());
+            }
+        }
+    }
+    let mut counts = counts
+        .into_iter()
+        .map(|(organization, titles)| {
+            let mut titles = titles.into_iter().collect::<Vec<String>>();
+            titles.sort();
+            (organization, titles)
+        })
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.name.cmp(&b.0.name)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(organizations: Vec<Organization>) -> Vec<String> {
+        organizations.into_iter().map(|o| o.name).collect()
+    }
+
+    #[test]
+    fn english_unit_stays_with_its_organization() {
+        let dictionary = AffiliationDictionary::bundled();
+        assert_eq!(
+            names(dictionary.organizations(
+                "Graduate School of Information Science and Technology, The University of Tokyo"
+            )),
+            vec!["東京大学"]
+        );
+        assert_eq!(
+            names(
+                dictionary.organizations("The University of Tokyo, Department of Computer Science")
+            ),
+            vec!["東京大学"]
+        );
+    }
+
+    #[test]
+    fn several_affiliations_are_split() {
+        let dictionary = AffiliationDictionary::bundled();
+        assert_eq!(
+            names(dictionary.organizations(
+                "Graduate School of Information Science and Technology, The University of Tokyo, RIKEN"
+            )),
+            vec!["東京大学", "理化学研究所"]
+        );
+        assert_eq!(
+            names(dictionary.organizations("東京大学大学院情報理工学系研究科、理化学研究所")),
+            vec!["東京大学", "理化学研究所"]
+        );
+    }
+
+    fn organization(affiliation: &str) -> Organization {
+        AffiliationDictionary::bundled()
+            .parse_hierarchy(affiliation)
+            .unwrap()
+            .organization
+    }
+
+    #[test]
+    fn aliases_are_canonicalized() {
+        let dictionary = AffiliationDictionary::bundled();
+        for alias in [
+            "東京大学",
+            "東大",
+            "The University of Tokyo",
+            "ＴＨＥ ＵＮＩＶＥＲＳＩＴＹ ＯＦ ＴＯＫＹＯ",
+        ] {
+            assert_eq!(
+                dictionary.canonicalize(alias).unwrap().name,
+                "東京大学",
+                "{}",
+                alias
+            );
+        }
+        assert_eq!(
+            organization("国立研究開発法人理化学研究所").name,
+            "理化学研究所"
+        );
+        assert_eq!(
+            organization("株式会社日立製作所 研究開発グループ").name,
+            "日立製作所"
+        );
+        assert_eq!(
+            organization("日立製作所研究開発グループ").name,
+            "日立製作所"
+        );
+        assert_eq!(organization("東大 工学系研究科").name, "東京大学");
+    }
+
+    #[test]
+    fn organization_types_are_classified() {
+        assert_eq!(organization("京大").org_type, OrgType::University);
+        assert_eq!(organization("NEC Corporation").org_type, OrgType::Company);
+        assert_eq!(organization("産総研").org_type, OrgType::NationalInstitute);
+        // not in the dictionary, guessed from keywords
+        assert_eq!(
+            organization("東京都立大学大学院システムデザイン研究科").org_type,
+            OrgType::University
+        );
+        assert_eq!(organization("株式会社ABEJA").org_type, OrgType::Company);
+        assert_eq!(
+            organization("国立研究開発法人防災科学技術研究所").org_type,
+            OrgType::NationalInstitute
+        );
+        assert_eq!(
+            organization("Carnegie Mellon University").org_type,
+            OrgType::University
+        );
+    }
+
+    #[test]
+    fn alias_prefix_must_end_at_a_unit_boundary() {
+        let todaiji = organization("東大寺");
+        assert_eq!(todaiji.name, "東大寺");
+        assert_eq!(todaiji.org_type, OrgType::Other);
+        assert_eq!(organization("北大路研究会").name, "北大路研究会");
+        assert_eq!(organization("日立ハイテク").name, "日立ハイテク");
+        assert_eq!(organization("東大 情報基盤センター").name, "東京大学");
+    }
+}
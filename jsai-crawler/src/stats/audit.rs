@@ -0,0 +1,247 @@
+use crate::stats::models::{Dataset, Session};
+use anyhow::Result;
+use fxhash::FxHashSet;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// z value of a 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Correct,
+    Incorrect,
+}
+
+/// A reviewer's verdict on the parsed authors of one sampled session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub session_id: String,
+    pub url: String,
+    pub seed: u64,
+    pub verdict: Verdict,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+    #[serde(default)]
+    pub reviewer: String,
+    /// Unix time of the review
+    pub reviewed_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditSummary {
+    pub reviewed: usize,
+    pub incorrect: usize,
+    pub error_rate: f64,
+    /// Wilson score interval of the error rate at 95% confidence
+    pub error_rate_low: f64,
+    pub error_rate_high: f64,
+}
+
+/// Path of the verdicts file of a dataset: `jsai2025.json` and `jsai2025.json.zst` have
+/// `jsai2025.audit.jsonl`.
+pub fn verdicts_path(dataset_path: &Path) -> PathBuf {
+    let name = dataset_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name.strip_suffix(".zst").unwrap_or(&name);
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    dataset_path.with_file_name(format!("{}.audit.jsonl", stem))
+}
+
+/// Draw `size` sessions with raw author text, reproducibly for a given `seed`.
+///
+/// Candidates are ordered by URL first, so that the sample does not depend on the order of the file.
+pub fn sample_sessions(dataset: &Dataset, size: usize, seed: u64) -> Vec<&Session> {
+    let mut candidates = dataset
+        .sessions
+        .iter()
+        .filter(|s| !s.raw_authors.is_empty())
+        .collect::<Vec<&Session>>();
+    candidates.sort_by(|a, b| a.url.cmp(&b.url));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut indices =
+        rand::seq::index::sample(&mut rng, candidates.len(), size.min(candidates.len())).into_vec();
+    indices.sort();
+    indices.into_iter().map(|i| candidates[i]).collect()
+}
+
+pub fn load_audit_records(file_path: &Path) -> Result<Vec<AuditRecord>> {
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    std::fs::read_to_string(file_path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+pub fn summarize_audit(records: &[AuditRecord]) -> AuditSummary {
+    let n = records.len();
+    let incorrect = records
+        .iter()
+        .filter(|r| r.verdict == Verdict::Incorrect)
+        .count();
+    if n == 0 {
+        return AuditSummary::default();
+    }
+    let p = incorrect as f64 / n as f64;
+    let n_f = n as f64;
+    let denominator = 1.0 + Z_95 * Z_95 / n_f;
+    let center = (p + Z_95 * Z_95 / (2.0 * n_f)) / denominator;
+    let margin =
+        Z_95 * (p * (1.0 - p) / n_f + Z_95 * Z_95 / (4.0 * n_f * n_f)).sqrt() / denominator;
+    AuditSummary {
+        reviewed: n,
+        incorrect,
+        error_rate: p,
+        error_rate_low: (center - margin).max(0.0),
+        error_rate_high: (center + margin).min(1.0),
+    }
+}
+
+fn print_session(session: &Session, number: usize, total: usize) {
+    println!();
+    println!("[{}/{}] {} {}", number, total, session.id, session.url);
+    println!("Raw:    {}", session.raw_authors);
+    if session.authors.is_empty() {
+        println!("Parsed: (no authors)");
+    }
+    for (i, author) in session.authors.iter().enumerate() {
+        let mark = match (author.is_presenter, author.is_student_presenter) {
+            (true, true) => "◎",
+            (true, false) => "○",
+            _ => " ",
+        };
+        let affiliations = author
+            .affiliations
+            .iter()
+            .map(|a| match a.index {
+                Some(index) => format!("{}. {}", index, a.name),
+                None => a.name.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join(" / ");
+        let label = if i == 0 { "Parsed:" } else { "" };
+        println!("{:<7} {}{} ({})", label, mark, author.name, affiliations);
+    }
+    if !session.review_issues.is_empty() {
+        println!("Issues: {}", session.review_issues.join("; "));
+    }
+}
+
+/// Show the sampled sessions that have no verdict yet and record the reviewer's verdicts to
+/// `verdicts_path` (JSON Lines), so that an interrupted audit can be resumed.
+pub fn run_audit(
+    dataset_path: &Path,
+    verdicts_path: &Path,
+    size: usize,
+    seed: u64,
+    reviewer: &str,
+) -> Result<AuditSummary> {
+    let dataset = Dataset::load(dataset_path)?;
+    let sample = sample_sessions(&dataset, size, seed);
+    let reviewed = load_audit_records(verdicts_path)?
+        .into_iter()
+        .filter(|r| r.seed == seed)
+        .map(|r| r.url)
+        .collect::<FxHashSet<String>>();
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(verdicts_path)?;
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    'sessions: for (i, session) in sample.iter().enumerate() {
+        if reviewed.contains(&session.url) {
+            continue;
+        }
+        print_session(session, i + 1, sample.len());
+        let verdict = loop {
+            print!("Correct? [y]es / [n]o / [s]kip / [q]uit: ");
+            std::io::stdout().flush()?;
+            let Some(answer) = lines.next().transpose()? else {
+                break 'sessions;
+            };
+            match answer.trim() {
+                "y" => break Verdict::Correct,
+                "n" => break Verdict::Incorrect,
+                "s" => continue 'sessions,
+                "q" => break 'sessions,
+                _ => continue,
+            }
+        };
+        let comment = if verdict == Verdict::Incorrect {
+            print!("What is wrong? ");
+            std::io::stdout().flush()?;
+            lines.next().transpose()?.unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        let record = AuditRecord {
+            session_id: session.id.clone(),
+            url: session.url.clone(),
+            seed,
+            verdict,
+            comment: comment.trim().to_string(),
+            reviewer: reviewer.to_string(),
+            reviewed_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+    }
+
+    let records = load_audit_records(verdicts_path)?
+        .into_iter()
+        .filter(|r| r.seed == seed)
+        .collect::<Vec<AuditRecord>>();
+    Ok(summarize_audit(&records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdicts_path_drops_the_dataset_extensions() {
+        assert_eq!(
+            verdicts_path(Path::new("data/jsai2025.json")),
+            PathBuf::from("data/jsai2025.audit.jsonl")
+        );
+        assert_eq!(
+            verdicts_path(Path::new("data/jsai2025.json.zst")),
+            PathBuf::from("data/jsai2025.audit.jsonl")
+        );
+    }
+
+    #[test]
+    fn sample_is_fixed_by_the_seed() {
+        let sessions = (0..20)
+            .map(|i| Session {
+                url: format!("https://example.com/{:02}", i),
+                raw_authors: "○山田 太郎 (東京大学)".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let dataset = Dataset::new(Vec::new(), sessions);
+        let urls = |seed| {
+            sample_sessions(&dataset, 5, seed)
+                .iter()
+                .map(|s| s.url[s.url.len() - 2..].to_string())
+                .collect::<Vec<String>>()
+        };
+        let sample = urls(42);
+        assert_eq!(sample.len(), 5);
+        assert!(sample.is_sorted());
+        assert_eq!(urls(42), sample);
+        assert_ne!(urls(7), sample);
+    }
+}
@@ -0,0 +1,596 @@
+use crate::llm::LlmBackend;
+use crate::llm::prompt::PromptTemplate;
+use crate::llm::usage::BudgetExceeded;
+use crate::mecab::{name_reading, romanize};
+use crate::shared::utils::create_progress_bar;
+use crate::stats::ai::parse_authors_with_llm;
+use crate::stats::identity::normalize_author_name;
+use crate::stats::models::{Affiliation, Author, AuthorProvenance, Dataset};
+use anyhow::{Result, anyhow};
+use futures::StreamExt;
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+pub type AuthorName = String;
+pub type AuthorAffiliation = String;
+
+/// Marks Confit puts in front of the presenting author.
+const PRESENTER_MARKS: &[char] = &['○', '〇', '◯', '◎', '*', '＊'];
+/// Presenter mark of a student presenter.
+const STUDENT_PRESENTER_MARK: char = '◎';
+/// Notes after a name marking a student presenter, after NFKC normalization.
+const STUDENT_NOTES: &[&str] = &["(学生)", "[学生]", "【学生】"];
+pub(crate) const SEPARATORS: &[char] = &[',', '、', '，', ';', '；'];
+
+/// Number of parsed sessions after which `parse_authors_in_dataset` saves its progress.
+const SAVE_INTERVAL: usize = 20;
+
+/// A name, its affiliation numbers and an optional student note such as `(学生)`.
+static AUTHOR_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<name>[^\d,、，;；]+?)\s*(?P<numbers>\d+(?:\s*[,、，]\s*\d+)*)?\s*(?:[(（\[【]学生[)）\]】]\s*)?(?:[,、，;；]\s*|$)")
+        .unwrap()
+});
+static AFFILIATION_NUMBER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[,、，;；]\s*)(?P<number>\d+)\s*[\.．:：]\s*").unwrap());
+
+fn is_open_paren(c: char) -> bool {
+    c == '(' || c == '（'
+}
+
+fn is_close_paren(c: char) -> bool {
+    c == ')' || c == '）'
+}
+
+fn normalize_spaces(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn trim_separators(text: &str) -> &str {
+    text.trim_matches(|c: char| c.is_whitespace() || SEPARATORS.contains(&c))
+}
+
+/// Split `text` into the author list and the trailing parenthesized affiliation block.
+fn split_affiliation_block(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim_end();
+    if !text.chars().last().is_some_and(is_close_paren) {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, c) in text.char_indices().rev() {
+        if is_close_paren(c) {
+            depth += 1;
+        } else if is_open_paren(c) {
+            depth -= 1;
+            if depth == 0 {
+                let inner = &text[i + c.len_utf8()..text.len() - text.chars().last()?.len_utf8()];
+                // `( 1. 東京大学, …)` has a space after the parenthesis that the numbered list must not see
+                return Some((text[..i].trim_end(), inner.trim()));
+            }
+        }
+    }
+    None
+}
+
+/// Parse `1. 東京大学、2. 理化学研究所` into a map from the index number to the affiliation.
+fn parse_numbered_affiliations(block: &str) -> Option<FxHashMap<u32, AuthorAffiliation>> {
+    let markers = AFFILIATION_NUMBER_REGEX
+        .captures_iter(block)
+        .map(|caps| {
+            let whole = caps.get(0).unwrap();
+            (
+                caps["number"].parse::<u32>().ok(),
+                whole.start(),
+                whole.end(),
+            )
+        })
+        .collect::<Vec<_>>();
+    if markers.is_empty() || markers[0].1 != 0 {
+        return None;
+    }
+
+    let mut affiliations = FxHashMap::default();
+    for (i, (number, _, end)) in markers.iter().enumerate() {
+        let next_start = markers.get(i + 1).map_or(block.len(), |m| m.1);
+        let affiliation = trim_separators(&block[*end..next_start]);
+        if affiliation.is_empty() {
+            return None;
+        }
+        affiliations.insert((*number)?, normalize_spaces(affiliation));
+    }
+    Some(affiliations)
+}
+
+/// Parse `○山田 太郎1,2、鈴木 花子2` into names with their affiliation numbers.
+fn parse_numbered_authors(text: &str) -> Result<Vec<(AuthorName, Vec<u32>)>> {
+    let mut authors = Vec::new();
+    let mut rest = trim_separators(text);
+    while !rest.is_empty() {
+        let caps = AUTHOR_REGEX
+            .captures(rest)
+            .ok_or_else(|| anyhow!("Unexpected author notation: {}", rest))?;
+        let name = caps["name"]
+            .trim()
+            .trim_start_matches(PRESENTER_MARKS)
+            .trim();
+        if name.is_empty() || name.chars().any(|c| is_open_paren(c) || is_close_paren(c)) {
+            return Err(anyhow!("Unexpected author name: {}", &caps["name"]));
+        }
+        let numbers = caps
+            .name("numbers")
+            .map(|m| {
+                m.as_str()
+                    .split(SEPARATORS)
+                    .filter_map(|n| n.trim().parse::<u32>().ok())
+                    .collect::<Vec<u32>>()
+            })
+            .unwrap_or_default();
+        authors.push((normalize_spaces(name), numbers));
+        rest = &rest[caps.get(0).unwrap().end()..];
+    }
+    if authors.is_empty() {
+        return Err(anyhow!("No authors found"));
+    }
+    Ok(authors)
+}
+
+/// Deterministic parser for the Confit author notation, e.g.
+/// `○山田 太郎1,2、鈴木 花子2 (1. 東京大学、2. 理化学研究所)`.
+///
+/// Affiliations keep their index numbers and the order in which they are listed for the author.
+/// Returns an error when the text does not follow the notation, so that the caller can fall back to the LLM.
+pub fn parse_authors_by_rules(text: &str) -> Result<Vec<Author>> {
+    let text = normalize_spaces(text);
+    let (authors_text, affiliation_block) = split_affiliation_block(&text)
+        .ok_or_else(|| anyhow!("No affiliation block found: {}", text))?;
+    let authors = parse_numbered_authors(authors_text)?;
+
+    let mut authors = match parse_numbered_affiliations(affiliation_block) {
+        Some(affiliations) => authors
+            .into_iter()
+            .map(|(name, numbers)| {
+                if numbers.is_empty() {
+                    return Err(anyhow!("Author without affiliation number: {}", name));
+                }
+                let affiliations = numbers
+                    .iter()
+                    .map(|n| {
+                        affiliations
+                            .get(n)
+                            .map(|affiliation| Affiliation::new(affiliation.clone(), Some(*n)))
+                            .ok_or_else(|| anyhow!("Unknown affiliation number {} for {}", n, name))
+                    })
+                    .collect::<Result<Vec<Affiliation>>>()?;
+                Ok(Author::new(name, affiliations))
+            })
+            .collect::<Result<Vec<Author>>>()?,
+        None => {
+            // a single unnumbered affiliation shared by every author
+            let affiliation = normalize_spaces(trim_separators(affiliation_block));
+            let ambiguous = authors.len() > 1 && affiliation.contains(SEPARATORS);
+            if affiliation.is_empty()
+                || ambiguous
+                || authors.iter().any(|(_, numbers)| !numbers.is_empty())
+            {
+                return Err(anyhow!(
+                    "Unexpected affiliation notation: {}",
+                    affiliation_block
+                ));
+            }
+            authors
+                .into_iter()
+                .map(|(name, _)| {
+                    Author::new(name, vec![Affiliation::new(affiliation.clone(), None)])
+                })
+                .collect()
+        }
+    };
+    mark_author_roles(&text, &mut authors);
+    Ok(authors)
+}
+
+/// Set the position of each author and find the presenter from the mark in front of their name in `text`.
+///
+/// Names are looked up in order, so that a name occurring inside an earlier one is not matched there.
+pub fn mark_author_roles(text: &str, authors: &mut [Author]) {
+    let text = normalize_for_match(text);
+    let mut offset = 0;
+    for (i, author) in authors.iter_mut().enumerate() {
+        author.position = Some(i as u32 + 1);
+        author.is_presenter = false;
+        author.is_student_presenter = false;
+        let name = normalize_for_match(&author.name);
+        if name.is_empty() {
+            continue;
+        }
+        let Some(start) = text[offset..].find(&name).map(|pos| offset + pos) else {
+            continue;
+        };
+        let end = start + name.len();
+        offset = end;
+
+        let mark = text[..start].chars().last();
+        let note = text[end..].trim_start_matches(|c: char| c.is_ascii_digit() || c == ',');
+        let is_student = mark == Some(STUDENT_PRESENTER_MARK)
+            || STUDENT_NOTES.iter().any(|n| note.starts_with(n));
+        author.is_presenter = mark.is_some_and(|c| PRESENTER_MARKS.contains(&c));
+        author.is_student_presenter = author.is_presenter && is_student;
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn is_latin_name(name: &str) -> bool {
+    name.nfkc()
+        .filter(|c| c.is_alphabetic())
+        .all(|c| c.is_ascii_alphabetic())
+}
+
+/// Set `romanized` from the author block of the page in Latin script, e.g. the English block
+/// shown next to the Japanese one, when it lists the same number of authors.
+pub fn fill_romanized_from_block(authors: &mut [Author], latin_block: &str) {
+    if !is_latin_name(latin_block) {
+        return;
+    }
+    let Ok(latin_authors) = parse_authors_by_rules(latin_block) else {
+        return;
+    };
+    if latin_authors.len() != authors.len() {
+        return;
+    }
+    for (author, latin) in authors.iter_mut().zip(latin_authors) {
+        if author.romanized.is_none() {
+            author.romanized = Some(normalize_author_name(&latin.name));
+        }
+    }
+}
+
+/// Fill `reading` and `romanized` of the authors that have none.
+///
+/// `romanized` is family name first, like the names of Japanese authors: a name already in Latin
+/// script, as shown on the pages of English papers, is reordered by [`normalize_author_name`];
+/// Japanese names get their reading from the vibrato tokenizer and are romanized from it.
+pub fn fill_name_readings(authors: &mut [Author]) {
+    for author in authors.iter_mut() {
+        let name = author.name.nfkc().collect::<String>();
+        if is_latin_name(&name) {
+            if author.romanized.is_none() && !name.trim().is_empty() {
+                author.romanized = Some(normalize_author_name(&name));
+            }
+            continue;
+        }
+        if author.reading.is_none() {
+            author.reading = name_reading(&name);
+        }
+        if author.romanized.is_none() {
+            author.romanized = author.reading.as_ref().map(|reading| {
+                reading
+                    .split(' ')
+                    .map(|part| capitalize(&romanize(part)))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedAuthors {
+    pub authors: Vec<Author>,
+    pub provenance: AuthorProvenance,
+    /// Problems found by [`validate_authors`]; non-empty means the result needs a human review
+    pub issues: Vec<String>,
+}
+
+pub(crate) fn normalize_for_match(text: &str) -> String {
+    text.nfkc()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Number of authors in `text`, when the author list follows the Confit notation.
+fn expected_author_count(text: &str) -> Option<usize> {
+    let text = normalize_spaces(text);
+    let (authors_text, _) = split_affiliation_block(&text)?;
+    parse_numbered_authors(authors_text)
+        .ok()
+        .map(|authors| authors.len())
+}
+
+/// Check LLM-parsed authors against the source text: every name and affiliation must occur in it
+/// (after NFKC normalization and removal of whitespace) and the number of authors must match.
+///
+/// Returns the list of problems found, empty when the authors are consistent with the text.
+pub fn validate_authors(text: &str, authors: &[Author]) -> Vec<String> {
+    let mut issues = Vec::new();
+    if authors.is_empty() {
+        issues.push("No authors returned".to_string());
+        return issues;
+    }
+
+    let normalized_text = normalize_for_match(text);
+    for author in authors {
+        let (name, affiliation) = (&author.name, author.affiliation());
+        if name.trim().is_empty() || !normalized_text.contains(&normalize_for_match(name)) {
+            issues.push(format!("Name not found in source text: {}", name));
+        }
+        let missing = affiliation
+            .split(SEPARATORS)
+            .map(normalize_for_match)
+            .any(|part| !part.is_empty() && !normalized_text.contains(&part));
+        if affiliation.trim().is_empty() || missing {
+            issues.push(format!(
+                "Affiliation not found in source text: {} ({})",
+                affiliation, name
+            ));
+        }
+    }
+    if let Some(expected) = expected_author_count(text)
+        && expected != authors.len()
+    {
+        issues.push(format!(
+            "Expected {} authors, got {}",
+            expected,
+            authors.len()
+        ));
+    }
+    issues
+}
+
+/// Parse the author text of a session with the rule-based parser, falling back to the LLM
+/// for inputs the rules cannot handle. LLM results are validated against the text.
+pub async fn parse_authors(
+    backend: &dyn LlmBackend,
+    prompt: &PromptTemplate,
+    text: &str,
+) -> Result<ParsedAuthors> {
+    if let Ok(authors) = parse_authors_by_rules(text) {
+        return Ok(ParsedAuthors {
+            authors,
+            provenance: AuthorProvenance::rules(),
+            issues: Vec::new(),
+        });
+    }
+    let mut authors = parse_authors_with_llm(backend, prompt, text).await?;
+    mark_author_roles(text, &mut authors);
+    let issues = validate_authors(text, &authors);
+    Ok(ParsedAuthors {
+        authors,
+        provenance: AuthorProvenance::llm(prompt, backend.model_id()),
+        issues,
+    })
+}
+
+/// Fill `authors` of the sessions in the dataset at `file_path` from their raw author text.
+///
+/// Sessions that already have authors are skipped unless `force` is set, and the progress is
+/// saved periodically, so an interrupted run can be resumed by running it again.
+pub async fn parse_authors_in_dataset(
+    backend: &dyn LlmBackend,
+    prompt: &PromptTemplate,
+    file_path: &Path,
+    concurrency: usize,
+    force: bool,
+) -> Result<()> {
+    let mut dataset = Dataset::load(file_path)?;
+    let targets: Vec<(usize, String)> = dataset
+        .sessions
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.raw_authors.is_empty() && (force || s.authors.is_empty()))
+        .map(|(i, s)| (i, s.raw_authors.clone()))
+        .collect();
+    let without_raw_text = dataset
+        .sessions
+        .iter()
+        .filter(|s| s.raw_authors.is_empty())
+        .count();
+    if without_raw_text > 0 {
+        println!(
+            "Skipping {} sessions without raw author text",
+            without_raw_text
+        );
+    }
+
+    let pb = create_progress_bar(targets.len(), Some("Parsing authors".to_string()));
+    let mut results =
+        futures::stream::iter(targets)
+            .map(|(i, raw_authors)| async move {
+                (i, parse_authors(backend, prompt, &raw_authors).await)
+            })
+            .buffer_unordered(concurrency.max(1));
+    let mut parsed = 0;
+    let mut failed = 0;
+    let mut flagged = 0;
+    while let Some((i, result)) = results.next().await {
+        match result {
+            Ok(ParsedAuthors {
+                authors,
+                provenance,
+                issues,
+            }) => {
+                let session = &mut dataset.sessions[i];
+                session.authors = authors;
+                fill_name_readings(&mut session.authors);
+                if !issues.is_empty() {
+                    flagged += 1;
+                    pb.println(format!(
+                        "Flagged for review ({}): {}",
+                        session.url,
+                        issues.join("; ")
+                    ));
+                }
+                session.review_issues = issues;
+                session.author_provenance = Some(provenance);
+                parsed += 1;
+                if parsed % SAVE_INTERVAL == 0 {
+                    dataset.save(file_path)?;
+                }
+            }
+            Err(e) if e.downcast_ref::<BudgetExceeded>().is_some() => {
+                // every remaining session would fail the same way; keep what is parsed so far
+                dataset.save(file_path)?;
+                pb.abandon_with_message(format!(
+                    "Parsed {} sessions ({} flagged for review), {} failed before the budget ran out",
+                    parsed, flagged, failed
+                ));
+                return Err(e);
+            }
+            Err(e) => {
+                failed += 1;
+                pb.println(format!(
+                    "Error parsing authors ({}): {}",
+                    dataset.sessions[i].url, e
+                ));
+            }
+        }
+        pb.inc(1);
+    }
+    dataset.save(file_path)?;
+    pb.finish_with_message(format!(
+        "Parsed {} sessions ({} flagged for review), {} failed",
+        parsed, flagged, failed
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::eval::GoldExample;
+
+    /// Made-up author blocks in the notations seen on Confit pages, labelled like the gold file.
+    const NOTATION_EXAMPLES: &str = r#"
+{"text": "○山田 太郎1,2、鈴木 花子2 (1. 東京大学、2. 理化学研究所)", "authors": [{"name": "山田 太郎", "affiliations": [{"name": "東京大学", "index": 1}, {"name": "理化学研究所", "index": 2}], "position": 1, "is_presenter": true}, {"name": "鈴木 花子", "affiliations": [{"name": "理化学研究所", "index": 2}], "position": 2}]}
+{"text": "○佐藤 一郎1、高橋 次郎1、田中 三郎2 (1. 京都大学大学院情報学研究科、2. 株式会社サンプル)", "authors": [{"name": "佐藤 一郎", "affiliations": [{"name": "京都大学大学院情報学研究科", "index": 1}], "position": 1, "is_presenter": true}, {"name": "高橋 次郎", "affiliations": [{"name": "京都大学大学院情報学研究科", "index": 1}], "position": 2}, {"name": "田中 三郎", "affiliations": [{"name": "株式会社サンプル", "index": 2}], "position": 3}]}
+{"text": "○伊藤 花子、渡辺 健（大阪大学）", "authors": [{"name": "伊藤 花子", "affiliations": [{"name": "大阪大学"}], "position": 1, "is_presenter": true}, {"name": "渡辺 健", "affiliations": [{"name": "大阪大学"}], "position": 2}]}
+{"text": "〇Taro Yamada1, Hanako Suzuki1,2 (1. The University of Tokyo, 2. RIKEN)", "authors": [{"name": "Taro Yamada", "affiliations": [{"name": "The University of Tokyo", "index": 1}], "position": 1, "is_presenter": true}, {"name": "Hanako Suzuki", "affiliations": [{"name": "The University of Tokyo", "index": 1}, {"name": "RIKEN", "index": 2}], "position": 2}]}
+{"text": "○中村 優1、小林 誠2、加藤 愛1,3 （1. 東北大学、2. (株)サンプル研究所、3. 産業技術総合研究所）", "authors": [{"name": "中村 優", "affiliations": [{"name": "東北大学", "index": 1}], "position": 1, "is_presenter": true}, {"name": "小林 誠", "affiliations": [{"name": "(株)サンプル研究所", "index": 2}], "position": 2}, {"name": "加藤 愛", "affiliations": [{"name": "東北大学", "index": 1}, {"name": "産業技術総合研究所", "index": 3}], "position": 3}]}
+{"text": "○吉田 翔 (名古屋大学)", "authors": [{"name": "吉田 翔", "affiliations": [{"name": "名古屋大学"}], "position": 1, "is_presenter": true}]}
+{"text": "○山本 陽子（九州大学）、松本 大輔（九州工業大学）", "authors": [{"name": "山本 陽子", "affiliations": [{"name": "九州大学"}], "position": 1, "is_presenter": true}, {"name": "松本 大輔", "affiliations": [{"name": "九州工業大学"}], "position": 2}]}
+{"text": "○井上 拓海、木村 彩 (北海道大学、北海道情報大学)", "authors": [{"name": "井上 拓海", "affiliations": [{"name": "北海道大学"}], "position": 1, "is_presenter": true}, {"name": "木村 彩", "affiliations": [{"name": "北海道情報大学"}], "position": 2}]}
+{"text": "○Kenji Sato1,2, Yuki Tanaka2, John Smith3 (1. Graduate School of Information Science and Technology, The University of Tokyo, 2. RIKEN Center for Advanced Intelligence Project, 3. Carnegie Mellon University)", "authors": [{"name": "Kenji Sato", "affiliations": [{"name": "Graduate School of Information Science and Technology, The University of Tokyo", "index": 1}, {"name": "RIKEN Center for Advanced Intelligence Project", "index": 2}], "position": 1, "is_presenter": true}, {"name": "Yuki Tanaka", "affiliations": [{"name": "RIKEN Center for Advanced Intelligence Project", "index": 2}], "position": 2}, {"name": "John Smith", "affiliations": [{"name": "Carnegie Mellon University", "index": 3}], "position": 3}]}
+{"text": "○Maria Garcia (Kyoto University)", "authors": [{"name": "Maria Garcia", "affiliations": [{"name": "Kyoto University"}], "position": 1, "is_presenter": true}]}
+{"text": "Hiroshi Kato1, ○Wei Zhang1, Akiko Mori2 (1. Tokyo Institute of Technology, 2. NTT Communication Science Laboratories)", "authors": [{"name": "Hiroshi Kato", "affiliations": [{"name": "Tokyo Institute of Technology", "index": 1}], "position": 1}, {"name": "Wei Zhang", "affiliations": [{"name": "Tokyo Institute of Technology", "index": 1}], "position": 2, "is_presenter": true}, {"name": "Akiko Mori", "affiliations": [{"name": "NTT Communication Science Laboratories", "index": 2}], "position": 3}]}
+{"text": "◎清水 健太1、岡田 真理子1,2、長谷川 修2,3 (1. 早稲田大学大学院基幹理工学研究科、2. 早稲田大学理工学術院、3. 国立情報学研究所)", "authors": [{"name": "清水 健太", "affiliations": [{"name": "早稲田大学大学院基幹理工学研究科", "index": 1}], "position": 1, "is_presenter": true, "is_student_presenter": true}, {"name": "岡田 真理子", "affiliations": [{"name": "早稲田大学大学院基幹理工学研究科", "index": 1}, {"name": "早稲田大学理工学術院", "index": 2}], "position": 2}, {"name": "長谷川 修", "affiliations": [{"name": "早稲田大学理工学術院", "index": 2}, {"name": "国立情報学研究所", "index": 3}], "position": 3}]}
+{"text": "○前田 智也1,2,3、森 洋平1 (1. 株式会社デンソーアイティーラボラトリ、2. 慶應義塾大学、3. 理化学研究所 革新知能統合研究センター)", "authors": [{"name": "前田 智也", "affiliations": [{"name": "株式会社デンソーアイティーラボラトリ", "index": 1}, {"name": "慶應義塾大学", "index": 2}, {"name": "理化学研究所 革新知能統合研究センター", "index": 3}], "position": 1, "is_presenter": true}, {"name": "森 洋平", "affiliations": [{"name": "株式会社デンソーアイティーラボラトリ", "index": 1}], "position": 2}]}
+{"text": "○藤田 悠1、Alexander Müller2、石井 亮1 (1. 筑波大学、2. Technical University of Munich)", "authors": [{"name": "藤田 悠", "affiliations": [{"name": "筑波大学", "index": 1}], "position": 1, "is_presenter": true}, {"name": "Alexander Müller", "affiliations": [{"name": "Technical University of Munich", "index": 2}], "position": 2}, {"name": "石井 亮", "affiliations": [{"name": "筑波大学", "index": 1}], "position": 3}]}
+{"text": "○林 美咲1(学生)、池田 浩2 (1. 東京工業大学、2. 東京大学)", "authors": [{"name": "林 美咲", "affiliations": [{"name": "東京工業大学", "index": 1}], "position": 1, "is_presenter": true, "is_student_presenter": true}, {"name": "池田 浩", "affiliations": [{"name": "東京大学", "index": 2}], "position": 2}]}
+{"text": "○橋本 直樹 （NEC）", "authors": [{"name": "橋本 直樹", "affiliations": [{"name": "NEC"}], "position": 1, "is_presenter": true}]}
+{"text": "○Taro A. Yamada1, Jean-Pierre Dubois2 (1. Dept. of Computer Science, Osaka University, 2. Inria)", "authors": [{"name": "Taro A. Yamada", "affiliations": [{"name": "Dept. of Computer Science, Osaka University", "index": 1}], "position": 1, "is_presenter": true}, {"name": "Jean-Pierre Dubois", "affiliations": [{"name": "Inria", "index": 2}], "position": 2}]}
+{"text": "○阿部 翼1、西村 涼子1、山口 大地1、坂本 拓也2、福田 恵3 （1. 名古屋工業大学、2. トヨタ自動車株式会社、3. 中部大学）", "authors": [{"name": "阿部 翼", "affiliations": [{"name": "名古屋工業大学", "index": 1}], "position": 1, "is_presenter": true}, {"name": "西村 涼子", "affiliations": [{"name": "名古屋工業大学", "index": 1}], "position": 2}, {"name": "山口 大地", "affiliations": [{"name": "名古屋工業大学", "index": 1}], "position": 3}, {"name": "坂本 拓也", "affiliations": [{"name": "トヨタ自動車株式会社", "index": 2}], "position": 4}, {"name": "福田 恵", "affiliations": [{"name": "中部大学", "index": 3}], "position": 5}]}
+{"text": "○Satoshi Ono (Fujitsu Limited)、Emily Brown (University of Cambridge)", "authors": [{"name": "Satoshi Ono", "affiliations": [{"name": "Fujitsu Limited"}], "position": 1, "is_presenter": true}, {"name": "Emily Brown", "affiliations": [{"name": "University of Cambridge"}], "position": 2}]}
+"#;
+
+    fn notation_examples() -> Vec<GoldExample> {
+        NOTATION_EXAMPLES
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    type AuthorSummary = (String, Vec<(String, Option<u32>)>, Option<u32>, bool, bool);
+
+    /// Names, affiliations with their indices, positions and presenter flags, for comparing authors.
+    fn summarize(authors: &[Author]) -> Vec<AuthorSummary> {
+        authors
+            .iter()
+            .map(|author| {
+                (
+                    author.name.clone(),
+                    author
+                        .affiliations
+                        .iter()
+                        .map(|a| (a.name.clone(), a.index))
+                        .collect(),
+                    author.position,
+                    author.is_presenter,
+                    author.is_student_presenter,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rules_agree_with_labelled_notations() {
+        let mut parsed = 0;
+        for example in notation_examples() {
+            // notations the rules do not handle are left to the LLM
+            let Ok(authors) = parse_authors_by_rules(&example.text) else {
+                continue;
+            };
+            assert_eq!(
+                summarize(&authors),
+                summarize(&example.authors),
+                "{}",
+                example.text
+            );
+            parsed += 1;
+        }
+        assert!(parsed > 0);
+    }
+
+    #[test]
+    fn rules_parse_numbered_notations() {
+        for example in notation_examples() {
+            if example
+                .authors
+                .iter()
+                .all(|a| a.affiliations.iter().all(|aff| aff.index.is_some()))
+            {
+                assert!(
+                    parse_authors_by_rules(&example.text).is_ok(),
+                    "{}",
+                    example.text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spaces_inside_the_affiliation_block_are_ignored() {
+        let authors =
+            parse_authors_by_rules("○山田 太郎1,2、鈴木 花子2 ( 1. 東京大学, 2. 理化学研究所 )")
+                .unwrap();
+        assert_eq!(
+            summarize(&authors),
+            vec![
+                (
+                    "山田 太郎".to_string(),
+                    vec![
+                        ("東京大学".to_string(), Some(1)),
+                        ("理化学研究所".to_string(), Some(2))
+                    ],
+                    Some(1),
+                    true,
+                    false
+                ),
+                (
+                    "鈴木 花子".to_string(),
+                    vec![("理化学研究所".to_string(), Some(2))],
+                    Some(2),
+                    false,
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_shared_affiliations_are_rejected() {
+        assert!(
+            parse_authors_by_rules("○井上 拓海、木村 彩 (北海道大学、北海道情報大学)").is_err()
+        );
+    }
+}
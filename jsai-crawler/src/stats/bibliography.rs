@@ -0,0 +1,287 @@
+use crate::stats::authors::is_latin_name;
+use crate::stats::identity::normalize_author_name;
+use crate::stats::models::{Author, Session};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+/// Proceedings of the JSAI annual conference, the `booktitle` of every entry.
+const BOOKTITLE: &str = "人工知能学会全国大会論文集";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BibFormat {
+    Bibtex,
+    Ris,
+    CslJson,
+}
+
+/// Sessions whose id is one of `ids` and whose title, abstract or keywords contain `query`
+/// (case-insensitive); an empty `ids` or no `query` does not restrict the sessions.
+pub fn select_sessions<'a>(
+    sessions: &'a [Session],
+    ids: &[String],
+    query: Option<&str>,
+) -> Vec<&'a Session> {
+    let query = query.map(|q| q.to_lowercase());
+    sessions
+        .iter()
+        .filter(|session| ids.is_empty() || ids.contains(&session.id))
+        .filter(|session| {
+            query.as_ref().is_none_or(|q| {
+                session.title.to_lowercase().contains(q)
+                    || session.abstract_text.to_lowercase().contains(q)
+                    || session
+                        .keywords
+                        .iter()
+                        .any(|k| k.to_lowercase().contains(q))
+            })
+        })
+        .collect()
+}
+
+/// Family and given name of an author, or `None` when the name cannot be split.
+///
+/// Names are split as stored, family name first: Japanese names as written, Latin names in
+/// `romanized`. Reordering them again would swap family and given name.
+fn split_name(author: &Author) -> Option<(String, String)> {
+    let name = if !is_latin_name(&author.name) {
+        author
+            .name
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    } else if let Some(romanized) = &author.romanized {
+        romanized.clone()
+    } else {
+        // not filled by `fill_name_readings` yet, so still in the order of the page
+        normalize_author_name(&author.name)
+    };
+    let (family, given) = name.split_once(' ')?;
+    Some((family.to_string(), given.to_string()))
+}
+
+/// Citation key, e.g. `jsai2024-1A3-GS-2-01`.
+fn citation_key(session: &Session) -> String {
+    let id = session
+        .id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>();
+    format!("{}{}-{}", session.event, session.year, id)
+}
+
+/// Escape the characters special to (Bib)TeX. Japanese text is left as is for upBibTeX and biber.
+fn escape_bibtex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+    {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '%' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape the characters of a URL that end or break a BibTeX field; the rest is left to `\url`.
+fn escape_bibtex_url(url: &str) -> String {
+    url.replace('%', "\\%").replace('#', "\\#")
+}
+
+/// An author of a BibTeX author list: `Family, Given`, or the whole name in braces when it cannot
+/// be split, so that BibTeX does not take part of it as the family name. A literal `and` in a name
+/// is braced so that it does not separate authors.
+fn bibtex_author(author: &Author) -> String {
+    let protect = |text: &str| {
+        text.split(' ')
+            .map(|word| {
+                if word.eq_ignore_ascii_case("and") {
+                    format!("{{{}}}", word)
+                } else {
+                    escape_bibtex(word)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    match split_name(author) {
+        Some((family, given)) => format!("{}, {}", protect(&family), protect(&given)),
+        None => format!("{{{}}}", escape_bibtex(&author.name)),
+    }
+}
+
+fn to_bibtex(session: &Session) -> String {
+    let mut fields = Vec::new();
+    if !session.authors.is_empty() {
+        let authors = session
+            .authors
+            .iter()
+            .map(bibtex_author)
+            .collect::<Vec<String>>()
+            .join(" and ");
+        fields.push(("author", authors));
+    }
+    // double braces keep the case of English titles
+    fields.push(("title", format!("{{{}}}", escape_bibtex(&session.title))));
+    fields.push(("booktitle", BOOKTITLE.to_string()));
+    fields.push(("year", session.year.to_string()));
+    fields.push(("number", escape_bibtex(&session.id)));
+    fields.push(("url", escape_bibtex_url(&session.url)));
+    if !session.keywords.is_empty() {
+        fields.push(("keywords", escape_bibtex(&session.keywords.join(", "))));
+    }
+    if !session.abstract_text.is_empty() {
+        fields.push(("abstract", escape_bibtex(&session.abstract_text)));
+    }
+
+    let body = fields
+        .iter()
+        .map(|(name, value)| format!("  {} = {{{}}}", name, value))
+        .collect::<Vec<String>>()
+        .join(",\n");
+    format!("@inproceedings{{{},\n{}\n}}\n", citation_key(session), body)
+}
+
+/// RIS values end at the line; newlines in a value would start a broken tag.
+fn ris_value(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn to_ris(session: &Session) -> String {
+    let mut lines = vec![("TY", "CPAPER".to_string())];
+    lines.push(("ID", citation_key(session)));
+    for author in &session.authors {
+        let name = match split_name(author) {
+            Some((family, given)) => format!("{}, {}", family, given),
+            None => author.name.clone(),
+        };
+        lines.push(("AU", ris_value(&name)));
+    }
+    lines.push(("TI", ris_value(&session.title)));
+    lines.push(("T2", BOOKTITLE.to_string()));
+    lines.push(("PY", session.year.to_string()));
+    lines.push(("M1", ris_value(&session.id)));
+    lines.push(("UR", session.url.clone()));
+    for keyword in &session.keywords {
+        lines.push(("KW", ris_value(keyword)));
+    }
+    if !session.abstract_text.is_empty() {
+        lines.push(("AB", ris_value(&session.abstract_text)));
+    }
+    lines.push(("ER", String::new()));
+    lines
+        .iter()
+        .map(|(tag, value)| format!("{}  - {}\n", tag, value))
+        .collect()
+}
+
+fn to_csl_json(session: &Session) -> Value {
+    let authors = session
+        .authors
+        .iter()
+        .map(|author| match split_name(author) {
+            Some((family, given)) => json!({"family": family, "given": given}),
+            None => json!({"literal": author.name}),
+        })
+        .collect::<Vec<Value>>();
+    let mut item = json!({
+        "id": citation_key(session),
+        "type": "paper-conference",
+        "title": session.title,
+        "container-title": BOOKTITLE,
+        "author": authors,
+        "issued": {"date-parts": [[session.year]]},
+        "number": session.id,
+        "URL": session.url,
+    });
+    if !session.keywords.is_empty() {
+        item["keyword"] = Value::from(session.keywords.join(", "));
+    }
+    if !session.abstract_text.is_empty() {
+        item["abstract"] = Value::from(session.abstract_text.clone());
+    }
+    item
+}
+
+/// Format the sessions as one bibliography in `format`.
+pub fn format_bibliography(sessions: &[&Session], format: BibFormat) -> Result<String> {
+    let text = match format {
+        BibFormat::Bibtex => sessions
+            .iter()
+            .map(|session| to_bibtex(session))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        BibFormat::Ris => sessions
+            .iter()
+            .map(|session| to_ris(session))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        BibFormat::CslJson => {
+            let items = sessions
+                .iter()
+                .map(|session| to_csl_json(session))
+                .collect::<Vec<Value>>();
+            serde_json::to_string_pretty(&items)? + "\n"
+        }
+    };
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn author(name: &str, romanized: Option<&str>) -> Author {
+        let mut author = Author::new(name.to_string(), Vec::new());
+        author.romanized = romanized.map(String::from);
+        author
+    }
+
+    fn names(family: &str, given: &str) -> Option<(String, String)> {
+        Some((family.to_string(), given.to_string()))
+    }
+
+    #[test]
+    fn names_are_split_family_name_first_as_stored() {
+        assert_eq!(
+            split_name(&author("山田 太郎", Some("Yamada Taro"))),
+            names("山田", "太郎")
+        );
+        assert_eq!(split_name(&author("山田太郎", None)), None);
+        assert_eq!(
+            split_name(&author("Taro Yamada", Some("Yamada Taro"))),
+            names("Yamada", "Taro")
+        );
+        // corrected by hand to the family name first, which must not be reordered again
+        assert_eq!(
+            split_name(&author("Zhang Wei", Some("Zhang Wei"))),
+            names("Zhang", "Wei")
+        );
+        assert_eq!(
+            split_name(&author("Taro Yamada", None)),
+            names("Yamada", "Taro")
+        );
+    }
+
+    #[test]
+    fn bibtex_url_is_escaped() {
+        let session = Session {
+            id: "1A1-GS-1-01".to_string(),
+            url: "https://example.com/a_b?q=%E5%AD%A6#top".to_string(),
+            ..Default::default()
+        };
+        assert!(
+            to_bibtex(&session).contains("url = {https://example.com/a_b?q=\\%E5\\%AD\\%A6\\#top}")
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::stats::models::Session;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchOp {
+    Add,
+    Remove,
+    Replace,
+}
+
+/// One operation of a correction, after JSON Patch (RFC 6902), e.g.
+/// `{"op": "replace", "path": "/authors/0/name", "value": "山田 太郎"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchOperation {
+    pub op: PatchOp,
+    /// JSON Pointer into the session; `-` as the last segment appends to an array
+    pub path: String,
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// Manual fix of one session, matched by its id or URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correction {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    pub patch: Vec<PatchOperation>,
+    /// Why the correction was made
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl Correction {
+    fn key(&self) -> String {
+        self.id
+            .clone()
+            .or_else(|| self.url.clone())
+            .unwrap_or_else(|| "<no id or url>".to_string())
+    }
+
+    fn matches(&self, session: &Session) -> bool {
+        (self.id.is_some() || self.url.is_some())
+            && self.id.as_ref().is_none_or(|id| *id == session.id)
+            && self.url.as_ref().is_none_or(|url| *url == session.url)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CorrectionReport {
+    pub applied: usize,
+    /// Corrections that match no session, e.g. because the session id changed in a new crawl
+    pub unmatched: Vec<String>,
+    /// Corrections whose patch could not be applied to the matched session
+    pub failed: Vec<String>,
+}
+
+/// Path of the corrections file of a dataset: `jsai2025.json` and `jsai2025.json.zst` have
+/// `jsai2025.corrections.json`.
+pub fn corrections_path(dataset_path: &Path) -> PathBuf {
+    let name = dataset_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name.strip_suffix(".zst").unwrap_or(&name);
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    dataset_path.with_file_name(format!("{}.corrections.json", stem))
+}
+
+pub fn load_corrections(file_path: &Path) -> Result<Vec<Correction>> {
+    let data = std::fs::read_to_string(file_path)?;
+    serde_json::from_str(&data)
+        .map_err(|e| anyhow!("Invalid corrections file {}: {}", file_path.display(), e))
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+fn apply_operation(target: &mut Value, operation: &PatchOperation) -> Result<()> {
+    let segments = operation
+        .path
+        .strip_prefix('/')
+        .ok_or_else(|| anyhow!("Path must start with '/': {}", operation.path))?
+        .split('/')
+        .map(unescape)
+        .collect::<Vec<String>>();
+    let (last, parents) = segments.split_last().unwrap();
+
+    let mut parent = target;
+    for segment in parents {
+        parent = match parent {
+            Value::Object(map) => map.get_mut(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("Path not found: {}", operation.path))?;
+    }
+
+    let not_found = || anyhow!("Path not found: {}", operation.path);
+    match (parent, operation.op) {
+        (Value::Object(map), PatchOp::Add) => {
+            map.insert(last.clone(), operation.value.clone());
+        }
+        (Value::Object(map), PatchOp::Replace) => {
+            *map.get_mut(last).ok_or_else(not_found)? = operation.value.clone();
+        }
+        (Value::Object(map), PatchOp::Remove) => {
+            map.remove(last).ok_or_else(not_found)?;
+        }
+        (Value::Array(items), PatchOp::Add) if last == "-" => items.push(operation.value.clone()),
+        (Value::Array(items), op) => {
+            let index = last.parse::<usize>().map_err(|_| not_found())?;
+            match op {
+                PatchOp::Add if index <= items.len() => {
+                    items.insert(index, operation.value.clone())
+                }
+                PatchOp::Replace if index < items.len() => items[index] = operation.value.clone(),
+                PatchOp::Remove if index < items.len() => {
+                    items.remove(index);
+                }
+                _ => return Err(not_found()),
+            }
+        }
+        _ => return Err(not_found()),
+    }
+    Ok(())
+}
+
+fn apply_correction(session: &mut Session, correction: &Correction) -> Result<()> {
+    let mut value = serde_json::to_value(&*session)?;
+    for operation in &correction.patch {
+        apply_operation(&mut value, operation)?;
+    }
+    *session = serde_json::from_value(value)?;
+    Ok(())
+}
+
+/// Apply the corrections to the sessions. A correction is applied to every session it matches,
+/// and all of its operations are applied or none.
+pub fn apply_corrections(sessions: &mut [Session], corrections: &[Correction]) -> CorrectionReport {
+    let mut report = CorrectionReport::default();
+    for correction in corrections {
+        let mut matched = false;
+        for session in sessions.iter_mut().filter(|s| correction.matches(s)) {
+            matched = true;
+            match apply_correction(session, correction) {
+                Ok(()) => report.applied += 1,
+                Err(e) => report.failed.push(format!("{}: {}", correction.key(), e)),
+            }
+        }
+        if !matched {
+            report.unmatched.push(correction.key());
+        }
+    }
+    report
+}
+
+/// Apply the corrections file next to the dataset at `dataset_path`, if there is one, and print
+/// which corrections no longer match to stderr.
+pub fn apply_corrections_for(dataset_path: &Path, sessions: &mut [Session]) -> Result<()> {
+    let path = corrections_path(dataset_path);
+    if !path.exists() {
+        return Ok(());
+    }
+    let corrections = load_corrections(&path)?;
+    let report = apply_corrections(sessions, &corrections);
+    eprintln!(
+        "Applied {} corrections from {}",
+        report.applied,
+        path.display()
+    );
+    for key in &report.unmatched {
+        eprintln!("Correction matches no session: {}", key);
+    }
+    for failure in &report.failed {
+        eprintln!("Correction could not be applied: {}", failure);
+    }
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use crate::stats::affiliations::AffiliationDictionary;
+use crate::stats::corrections::apply_corrections_for;
+use crate::stats::loader::dataset_files;
+use crate::stats::migrations::parse_event;
+use crate::stats::models::{Dataset, Section, Session};
+use anyhow::Result;
+use clap::ValueEnum;
+use fxhash::FxHashMap;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+use std::path::{Path, PathBuf};
+
+/// Normalized layout of the exported database. Integer foreign keys end in `_row_id`, so that they
+/// are not mistaken for the Confit ids (`section_id`, `session_id`) and resolved author ids
+/// (`author_id`) stored as text. `sessions_fts` indexes titles and abstracts by trigrams, which
+/// works for Japanese text without a tokenizer but never matches terms shorter than 3 characters.
+const SCHEMA: &str = "
+CREATE TABLE sections (
+    id INTEGER PRIMARY KEY,
+    event TEXT NOT NULL,
+    year INTEGER NOT NULL,
+    section_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL UNIQUE,
+    time TEXT NOT NULL
+);
+CREATE TABLE sessions (
+    id INTEGER PRIMARY KEY,
+    event TEXT NOT NULL,
+    year INTEGER NOT NULL,
+    session_id TEXT NOT NULL,
+    track_type TEXT,
+    track TEXT,
+    title TEXT NOT NULL,
+    url TEXT NOT NULL UNIQUE,
+    time TEXT NOT NULL,
+    abstract TEXT NOT NULL,
+    raw_authors TEXT NOT NULL,
+    section_row_id INTEGER REFERENCES sections(id)
+);
+CREATE TABLE keywords (
+    session_row_id INTEGER NOT NULL REFERENCES sessions(id),
+    position INTEGER NOT NULL,
+    keyword TEXT NOT NULL,
+    PRIMARY KEY (session_row_id, position)
+);
+CREATE TABLE authors (
+    id INTEGER PRIMARY KEY,
+    session_row_id INTEGER NOT NULL REFERENCES sessions(id),
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    author_id TEXT,
+    reading TEXT,
+    romanized TEXT,
+    orcid TEXT,
+    researchmap_id TEXT,
+    is_presenter INTEGER NOT NULL,
+    is_student_presenter INTEGER NOT NULL
+);
+CREATE TABLE affiliations (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    organization TEXT,
+    org_type TEXT
+);
+CREATE TABLE author_affiliations (
+    author_row_id INTEGER NOT NULL REFERENCES authors(id),
+    affiliation_row_id INTEGER NOT NULL REFERENCES affiliations(id),
+    position INTEGER NOT NULL,
+    affiliation_index INTEGER,
+    PRIMARY KEY (author_row_id, position)
+);
+CREATE INDEX sessions_year ON sessions(year);
+CREATE INDEX authors_session ON authors(session_row_id);
+CREATE INDEX authors_author_id ON authors(author_id);
+CREATE VIRTUAL TABLE sessions_fts USING fts5(
+    title, abstract, content = 'sessions', content_rowid = 'id', tokenize = 'trigram'
+);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum QueryFormat {
+    Table,
+    Csv,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportReport {
+    pub sections: usize,
+    pub sessions: usize,
+    pub authors: usize,
+    pub affiliations: usize,
+    /// Sessions of the same URL in several datasets, of which the first is kept
+    pub duplicates: usize,
+}
+
+struct Exporter<'a> {
+    conn: &'a Connection,
+    dictionary: &'a AffiliationDictionary,
+    section_ids: FxHashMap<String, i64>,
+    affiliation_ids: FxHashMap<String, i64>,
+    report: ExportReport,
+}
+
+impl Exporter<'_> {
+    fn insert_section(&mut self, section: &Section, event: &str, year: u32) -> Result<i64> {
+        if let Some(id) = self.section_ids.get(&section.url) {
+            return Ok(*id);
+        }
+        let (event, year) = parse_event(&section.url).unwrap_or((event.to_string(), year));
+        self.conn.execute(
+            "INSERT INTO sections (event, year, section_id, title, url, time) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![event, year, section.id, section.title, section.url, section.time],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.section_ids.insert(section.url.clone(), id);
+        self.report.sections += 1;
+        Ok(id)
+    }
+
+    fn insert_affiliation(&mut self, name: &str) -> Result<i64> {
+        if let Some(id) = self.affiliation_ids.get(name) {
+            return Ok(*id);
+        }
+        let organization = self.dictionary.organizations(name).into_iter().next();
+        self.conn.execute(
+            "INSERT INTO affiliations (name, organization, org_type) VALUES (?1, ?2, ?3)",
+            params![
+                name,
+                organization.as_ref().map(|o| o.name.clone()),
+                organization.as_ref().map(|o| o.org_type.label()),
+            ],
+        )?;
+        let id = self.conn.last_insert_rowid();
+        self.affiliation_ids.insert(name.to_string(), id);
+        self.report.affiliations += 1;
+        Ok(id)
+    }
+
+    fn insert_session(&mut self, session: &Session) -> Result<()> {
+        let exists = self
+            .conn
+            .query_row(
+                "SELECT id FROM sessions WHERE url = ?1",
+                params![session.url],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        if exists.is_some() {
+            self.report.duplicates += 1;
+            return Ok(());
+        }
+
+        let section_row = self.insert_section(&session.section, &session.event, session.year)?;
+        let parsed_id = session.parsed_id().ok();
+        self.conn.execute(
+            "INSERT INTO sessions (event, year, session_id, track_type, track, title, url, time, abstract,
+                raw_authors, section_row_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                session.event,
+                session.year,
+                session.id,
+                parsed_id.as_ref().map(|id| id.track_type.label()),
+                parsed_id.as_ref().map(|id| id.track()),
+                session.title,
+                session.url,
+                session.time,
+                session.abstract_text,
+                session.raw_authors,
+                section_row,
+            ],
+        )?;
+        let session_row = self.conn.last_insert_rowid();
+        self.report.sessions += 1;
+
+        for (i, keyword) in session.keywords.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO keywords (session_row_id, position, keyword) VALUES (?1, ?2, ?3)",
+                params![session_row, i + 1, keyword],
+            )?;
+        }
+        for (i, author) in session.authors.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO authors (session_row_id, position, name, author_id, reading, romanized, orcid,
+                    researchmap_id, is_presenter, is_student_presenter)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    session_row,
+                    author.position.unwrap_or(i as u32 + 1),
+                    author.name,
+                    author.author_id,
+                    author.reading,
+                    author.romanized,
+                    author.orcid,
+                    author.researchmap_id,
+                    author.is_presenter,
+                    author.is_student_presenter,
+                ],
+            )?;
+            let author_row = self.conn.last_insert_rowid();
+            self.report.authors += 1;
+            for (j, affiliation) in author.affiliations.iter().enumerate() {
+                let affiliation_row = self.insert_affiliation(&affiliation.name)?;
+                self.conn.execute(
+                    "INSERT INTO author_affiliations (author_row_id, affiliation_row_id, position, affiliation_index)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![author_row, affiliation_row, j + 1, affiliation.index],
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_database(
+    conn: &mut Connection,
+    files: Vec<PathBuf>,
+    dictionary: &AffiliationDictionary,
+) -> Result<ExportReport> {
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    conn.execute_batch(SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    let mut exporter = Exporter {
+        conn: &tx,
+        dictionary,
+        section_ids: FxHashMap::default(),
+        affiliation_ids: FxHashMap::default(),
+        report: ExportReport::default(),
+    };
+    for file_path in files {
+        let mut dataset = Dataset::load(&file_path)?;
+        apply_corrections_for(&file_path, &mut dataset.sessions)?;
+        // sections without sessions are kept too; they take the event of the dataset
+        let (event, year) = dataset
+            .sessions
+            .first()
+            .map(|s| (s.event.clone(), s.year))
+            .unwrap_or_default();
+        for section in &dataset.sections {
+            exporter.insert_section(section, &event, year)?;
+        }
+        for session in &dataset.sessions {
+            exporter.insert_session(session)?;
+        }
+    }
+    let report = exporter.report;
+    tx.execute(
+        "INSERT INTO sessions_fts (sessions_fts) VALUES ('rebuild')",
+        [],
+    )?;
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Write the sessions of the datasets in `paths`, with their corrections applied, to a new SQLite
+/// database at `db_path`, replacing an existing file.
+///
+/// The database is built in `<db_path>.tmp` and renamed when complete, so that a failed export
+/// leaves the existing database as it was.
+pub fn export_database(
+    paths: &[PathBuf],
+    db_path: &Path,
+    dictionary: &AffiliationDictionary,
+) -> Result<ExportReport> {
+    let files = dataset_files(paths)?;
+    let mut tmp_path = db_path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    // left over from an interrupted export
+    if tmp_path.exists() {
+        std::fs::remove_file(&tmp_path)?;
+    }
+
+    let mut conn = Connection::open(&tmp_path)?;
+    let report = write_database(&mut conn, files, dictionary).and_then(|report| {
+        conn.close().map_err(|(_, e)| e)?;
+        Ok(report)
+    });
+    match report {
+        Ok(report) => {
+            std::fs::rename(&tmp_path, db_path)?;
+            Ok(report)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+fn format_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).to_string(),
+        ValueRef::Blob(blob) => format!("<{} bytes>", blob.len()),
+    }
+}
+
+/// Run `sql` on the database at `db_path` and print the rows as a table or as CSV.
+/// Returns the number of rows.
+pub fn query_database(db_path: &Path, sql: &str, format: QueryFormat) -> Result<usize> {
+    if !db_path.exists() {
+        return Err(anyhow::anyhow!(
+            "No database at {}, run `db export` first",
+            db_path.display()
+        ));
+    }
+    // a query must not change the exported database
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(sql)?;
+    let columns = stmt
+        .column_names()
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+    let mut rows = stmt.query([])?;
+    let mut records = Vec::new();
+    while let Some(row) = rows.next()? {
+        let record = (0..columns.len())
+            .map(|i| Ok(format_value(row.get_ref(i)?)))
+            .collect::<Result<Vec<String>>>()?;
+        records.push(record);
+    }
+
+    match format {
+        QueryFormat::Table => {
+            let mut table = comfy_table::Table::new();
+            table.load_preset(comfy_table::presets::UTF8_FULL_CONDENSED);
+            table.set_header(&columns);
+            for record in &records {
+                table.add_row(record);
+            }
+            println!("{}", table);
+        }
+        QueryFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(std::io::stdout());
+            writer.write_record(&columns)?;
+            for record in &records {
+                writer.write_record(record)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(records.len())
+}
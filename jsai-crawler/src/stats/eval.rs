@@ -0,0 +1,269 @@
+use crate::llm::LlmBackend;
+use crate::llm::prompt::PromptTemplate;
+use crate::stats::ai::parse_authors_with_llm;
+use crate::stats::authors::{
+    SEPARATORS, mark_author_roles, normalize_for_match, parse_authors, parse_authors_by_rules,
+};
+use crate::stats::models::{Author, Session};
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use once_cell::sync::Lazy;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// `1,2` after a name: an author with several affiliation numbers.
+static AFFILIATION_NUMBERS_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\d\s*[,，]\s*\d").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuthorParserKind {
+    /// The rule-based parser only
+    Rules,
+    /// The LLM only
+    Llm,
+    /// The rule-based parser with the LLM as fallback, as used by `parse-authors`
+    Auto,
+}
+
+/// One labelled author block of the gold file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GoldExample {
+    pub text: String,
+    pub authors: Vec<Author>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EvalReport {
+    pub examples: usize,
+    pub errors: usize,
+    pub exact_match: f64,
+    pub name_precision: f64,
+    pub name_recall: f64,
+    /// Share of correctly found names whose affiliation is also correct
+    pub affiliation_accuracy: f64,
+    /// Share of correctly found names whose presenter flag is also correct
+    pub presenter_accuracy: f64,
+    pub mismatches: Vec<String>,
+}
+
+/// Read a JSON Lines file of [`GoldExample`]. An empty file is an error, as no score can be computed.
+pub fn load_gold_examples(file_path: &Path) -> Result<Vec<GoldExample>> {
+    let examples = std::fs::read_to_string(file_path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect::<Result<Vec<GoldExample>>>()?;
+    if examples.is_empty() {
+        bail!(
+            "no gold examples; draw candidates from crawled datasets with `sample-gold` and add them once corrected by hand"
+        );
+    }
+    Ok(examples)
+}
+
+/// Author notations the gold file should cover, so that a sample is not all the common case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Notation {
+    /// Latin-script names, as on the pages of English papers
+    English,
+    /// At least one author with several affiliations
+    MultiAffiliation,
+    Other,
+}
+
+impl Notation {
+    const ALL: [Notation; 3] = [
+        Notation::English,
+        Notation::MultiAffiliation,
+        Notation::Other,
+    ];
+
+    fn of(session: &Session) -> Self {
+        let (latin, letters) = session
+            .raw_authors
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .fold((0, 0), |(latin, letters), c| {
+                (latin + usize::from(c.is_ascii_alphabetic()), letters + 1)
+            });
+        if letters > 0 && latin * 2 > letters {
+            Notation::English
+        } else if session.authors.iter().any(|a| a.affiliations.len() > 1)
+            || AFFILIATION_NUMBERS_REGEX.is_match(&session.raw_authors)
+        {
+            Notation::MultiAffiliation
+        } else {
+            Notation::Other
+        }
+    }
+}
+
+/// Draw about `size` author blocks from crawled sessions as candidates for the gold file,
+/// reproducibly for a given `seed`.
+///
+/// English and multi-affiliation blocks are drawn as often as the rest, although they are rarer.
+/// The parsed authors of a session are its draft labels, to be corrected by hand before the
+/// candidates are added to the gold file.
+pub fn sample_gold_candidates(sessions: &[Session], size: usize, seed: u64) -> Vec<GoldExample> {
+    let mut candidates = sessions
+        .iter()
+        .filter(|s| !s.raw_authors.trim().is_empty())
+        .collect::<Vec<&Session>>();
+    candidates.sort_by(|a, b| a.url.cmp(&b.url));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let per_notation = size.div_ceil(Notation::ALL.len());
+
+    let mut sampled = Vec::new();
+    for notation in Notation::ALL {
+        let group = candidates
+            .iter()
+            .filter(|s| Notation::of(s) == notation)
+            .copied()
+            .collect::<Vec<&Session>>();
+        let mut indices =
+            rand::seq::index::sample(&mut rng, group.len(), per_notation.min(group.len()))
+                .into_vec();
+        indices.sort();
+        sampled.extend(indices.into_iter().map(|i| group[i]));
+    }
+    sampled.truncate(size);
+
+    sampled
+        .into_iter()
+        .map(|session| GoldExample {
+            text: session.raw_authors.clone(),
+            authors: session
+                .authors
+                .iter()
+                .map(|author| {
+                    let mut label = Author::new(author.name.clone(), author.affiliations.clone());
+                    label.position = author.position;
+                    label.is_presenter = author.is_presenter;
+                    label.is_student_presenter = author.is_student_presenter;
+                    label
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Write gold examples as JSON Lines.
+pub fn write_gold_examples(file_path: &Path, examples: &[GoldExample]) -> Result<()> {
+    let mut file = std::fs::File::create(file_path)?;
+    for example in examples {
+        writeln!(file, "{}", serde_json::to_string(example)?)?;
+    }
+    Ok(())
+}
+
+/// Affiliations of an author as a sorted list, so that a joined string and a list compare equal.
+fn affiliation_key(author: &Author) -> Vec<String> {
+    let mut parts = author
+        .affiliations
+        .iter()
+        .flat_map(|affiliation| affiliation.name.split(SEPARATORS))
+        .map(normalize_for_match)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<String>>();
+    parts.sort();
+    parts
+}
+
+async fn run_parser(
+    kind: AuthorParserKind,
+    backend: Option<&dyn LlmBackend>,
+    prompt: &PromptTemplate,
+    text: &str,
+) -> Result<Vec<Author>> {
+    match (kind, backend) {
+        (AuthorParserKind::Rules, _) => parse_authors_by_rules(text),
+        (AuthorParserKind::Llm, Some(backend)) => {
+            let mut authors = parse_authors_with_llm(backend, prompt, text).await?;
+            mark_author_roles(text, &mut authors);
+            Ok(authors)
+        }
+        (AuthorParserKind::Auto, Some(backend)) => {
+            Ok(parse_authors(backend, prompt, text).await?.authors)
+        }
+        (_, None) => Err(anyhow::anyhow!("{:?} parser requires an LLM backend", kind)),
+    }
+}
+
+/// Run the parser over the gold examples and compare the results with the labels.
+pub async fn evaluate_author_parser(
+    kind: AuthorParserKind,
+    backend: Option<&dyn LlmBackend>,
+    prompt: &PromptTemplate,
+    examples: &[GoldExample],
+) -> Result<EvalReport> {
+    let mut report = EvalReport {
+        examples: examples.len(),
+        ..Default::default()
+    };
+    let mut exact = 0;
+    let (mut predicted_names, mut gold_names, mut correct_names) = (0, 0, 0);
+    let mut correct_affiliations = 0;
+    let mut correct_presenters = 0;
+
+    for example in examples {
+        let predicted = match run_parser(kind, backend, prompt, &example.text).await {
+            Ok(predicted) => predicted,
+            Err(e) => {
+                report.errors += 1;
+                report
+                    .mismatches
+                    .push(format!("{} => error: {}", example.text, e));
+                Vec::new()
+            }
+        };
+        let presenters: Vec<(String, bool)> = predicted
+            .iter()
+            .map(|a| (normalize_for_match(&a.name), a.is_presenter))
+            .collect();
+        let predicted: Vec<(String, Vec<String>)> = predicted
+            .iter()
+            .map(|a| (normalize_for_match(&a.name), affiliation_key(a)))
+            .collect();
+        let gold: Vec<(String, Vec<String>)> = example
+            .authors
+            .iter()
+            .map(|a| (normalize_for_match(&a.name), affiliation_key(a)))
+            .collect();
+
+        if predicted == gold {
+            exact += 1;
+        } else if !predicted.is_empty() {
+            report
+                .mismatches
+                .push(format!("{} => {:?}", example.text, predicted));
+        }
+
+        predicted_names += predicted.len();
+        gold_names += gold.len();
+        let mut unmatched = predicted.iter().zip(&presenters).collect::<Vec<_>>();
+        for ((name, affiliation), author) in gold.iter().zip(&example.authors) {
+            if let Some(pos) = unmatched.iter().position(|((n, _), _)| n == name) {
+                let ((_, predicted_affiliation), (_, is_presenter)) = unmatched.remove(pos);
+                correct_names += 1;
+                if predicted_affiliation == affiliation {
+                    correct_affiliations += 1;
+                }
+                if *is_presenter == author.is_presenter {
+                    correct_presenters += 1;
+                }
+            }
+        }
+    }
+
+    let ratio = |a: usize, b: usize| if b == 0 { 0.0 } else { a as f64 / b as f64 };
+    report.exact_match = ratio(exact, examples.len());
+    report.name_precision = ratio(correct_names, predicted_names);
+    report.name_recall = ratio(correct_names, gold_names);
+    report.affiliation_accuracy = ratio(correct_affiliations, correct_names);
+    report.presenter_accuracy = ratio(correct_presenters, correct_names);
+    Ok(report)
+}
@@ -0,0 +1,494 @@
+use crate::stats::models::Session;
+use anyhow::{Result, anyhow};
+use arrow_array::{ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use clap::ValueEnum;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Separator of list columns such as `keywords` and `affiliations` in the sessions and authors tables.
+const LIST_SEPARATOR: &str = "; ";
+
+/// Columns every table has, to join the tables; selecting them alone does not select a table.
+const KEY_COLUMNS: [&str; 4] = ["event", "year", "session_id", "url"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportTable {
+    /// One row per session
+    Sessions,
+    /// One row per author of a session
+    Authors,
+    /// One row per keyword of a session
+    Keywords,
+}
+
+impl ExportTable {
+    fn name(&self) -> &'static str {
+        match self {
+            ExportTable::Sessions => "sessions",
+            ExportTable::Authors => "authors",
+            ExportTable::Keywords => "keywords",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Text,
+    Int,
+    Bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Null,
+    Text(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<Option<String>> for Cell {
+    fn from(text: Option<String>) -> Self {
+        text.map_or(Cell::Null, Cell::Text)
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Int(value)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(value: bool) -> Self {
+        Cell::Bool(value)
+    }
+}
+
+impl Cell {
+    fn to_csv(&self) -> String {
+        match self {
+            Cell::Null => String::new(),
+            Cell::Text(text) => text.clone(),
+            Cell::Int(value) => value.to_string(),
+            Cell::Bool(value) => value.to_string(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Cell::Null => Value::Null,
+            Cell::Text(text) => Value::from(text.clone()),
+            Cell::Int(value) => Value::from(*value),
+            Cell::Bool(value) => Value::from(*value),
+        }
+    }
+}
+
+/// A flat table: the column names and types, and rows of cells in the same order.
+struct Table {
+    columns: Vec<(&'static str, ColumnType)>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    fn new(columns: &[(&'static str, ColumnType)]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn has_column(&self, name: &str) -> bool {
+        self.columns.iter().any(|(column, _)| *column == name)
+    }
+
+    /// Whether `selection` asks for a column of this table other than the [`KEY_COLUMNS`].
+    fn is_selected_by(&self, selection: &[String]) -> bool {
+        selection
+            .iter()
+            .any(|name| !KEY_COLUMNS.contains(&name.as_str()) && self.has_column(name))
+    }
+
+    /// Keep the columns in `selection`, in the order of the selection.
+    fn select(self, selection: &[String]) -> Self {
+        let indices = selection
+            .iter()
+            .filter_map(|name| self.columns.iter().position(|(column, _)| column == name))
+            .collect::<Vec<usize>>();
+        Self {
+            columns: indices.iter().map(|i| self.columns[*i]).collect(),
+            rows: self
+                .rows
+                .into_iter()
+                .map(|row| indices.iter().map(|i| row[*i].clone()).collect())
+                .collect(),
+        }
+    }
+}
+
+const SESSION_COLUMNS: [(&str, ColumnType); 15] = [
+    ("event", ColumnType::Text),
+    ("year", ColumnType::Int),
+    ("session_id", ColumnType::Text),
+    ("track_type", ColumnType::Text),
+    ("track", ColumnType::Text),
+    ("title", ColumnType::Text),
+    ("url", ColumnType::Text),
+    ("time", ColumnType::Text),
+    ("section_id", ColumnType::Text),
+    ("section_title", ColumnType::Text),
+    ("abstract", ColumnType::Text),
+    ("raw_authors", ColumnType::Text),
+    ("authors", ColumnType::Text),
+    ("author_count", ColumnType::Int),
+    ("keywords", ColumnType::Text),
+];
+
+const AUTHOR_COLUMNS: [(&str, ColumnType); 14] = [
+    ("event", ColumnType::Text),
+    ("year", ColumnType::Int),
+    ("session_id", ColumnType::Text),
+    ("position", ColumnType::Int),
+    ("name", ColumnType::Text),
+    ("author_id", ColumnType::Text),
+    ("reading", ColumnType::Text),
+    ("romanized", ColumnType::Text),
+    ("affiliations", ColumnType::Text),
+    ("is_presenter", ColumnType::Bool),
+    ("is_student_presenter", ColumnType::Bool),
+    ("orcid", ColumnType::Text),
+    ("researchmap_id", ColumnType::Text),
+    ("url", ColumnType::Text),
+];
+
+const KEYWORD_COLUMNS: [(&str, ColumnType); 6] = [
+    ("event", ColumnType::Text),
+    ("year", ColumnType::Int),
+    ("session_id", ColumnType::Text),
+    ("position", ColumnType::Int),
+    ("keyword", ColumnType::Text),
+    ("url", ColumnType::Text),
+];
+
+fn build_table(table: ExportTable, sessions: &[Session]) -> Table {
+    match table {
+        ExportTable::Sessions => {
+            let mut result = Table::new(&SESSION_COLUMNS);
+            for session in sessions {
+                let parsed_id = session.parsed_id().ok();
+                result.rows.push(vec![
+                    session.event.clone().into(),
+                    (session.year as i64).into(),
+                    session.id.clone().into(),
+                    parsed_id.as_ref().map(|id| id.track_type.label()).into(),
+                    parsed_id.as_ref().map(|id| id.track()).into(),
+                    session.title.clone().into(),
+                    session.url.clone().into(),
+                    session.time.clone().into(),
+                    session.section.id.clone().into(),
+                    session.section.title.clone().into(),
+                    session.abstract_text.clone().into(),
+                    session.raw_authors.clone().into(),
+                    session
+                        .authors
+                        .iter()
+                        .map(|a| a.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(LIST_SEPARATOR)
+                        .into(),
+                    (session.authors.len() as i64).into(),
+                    session.keywords.join(LIST_SEPARATOR).into(),
+                ]);
+            }
+            result
+        }
+        ExportTable::Authors => {
+            let mut result = Table::new(&AUTHOR_COLUMNS);
+            for session in sessions {
+                for (i, author) in session.authors.iter().enumerate() {
+                    result.rows.push(vec![
+                        session.event.clone().into(),
+                        (session.year as i64).into(),
+                        session.id.clone().into(),
+                        (author.position.map_or(i as i64 + 1, i64::from)).into(),
+                        author.name.clone().into(),
+                        author.author_id.clone().into(),
+                        author.reading.clone().into(),
+                        author.romanized.clone().into(),
+                        author
+                            .affiliations
+                            .iter()
+                            .map(|a| a.name.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(LIST_SEPARATOR)
+                            .into(),
+                        author.is_presenter.into(),
+                        author.is_student_presenter.into(),
+                        author.orcid.clone().into(),
+                        author.researchmap_id.clone().into(),
+                        session.url.clone().into(),
+                    ]);
+                }
+            }
+            result
+        }
+        ExportTable::Keywords => {
+            let mut result = Table::new(&KEYWORD_COLUMNS);
+            for session in sessions {
+                for (i, keyword) in session.keywords.iter().enumerate() {
+                    result.rows.push(vec![
+                        session.event.clone().into(),
+                        (session.year as i64).into(),
+                        session.id.clone().into(),
+                        (i as i64 + 1).into(),
+                        keyword.clone().into(),
+                        session.url.clone().into(),
+                    ]);
+                }
+            }
+            result
+        }
+    }
+}
+
+fn write_csv(table: &Table, file_path: &Path) -> Result<()> {
+    let mut writer = csv::Writer::from_path(file_path)?;
+    writer.write_record(table.columns.iter().map(|(name, _)| *name))?;
+    for row in &table.rows {
+        writer.write_record(row.iter().map(Cell::to_csv))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_jsonl(table: &Table, file_path: &Path) -> Result<()> {
+    let mut lines = String::new();
+    for row in &table.rows {
+        let object = table
+            .columns
+            .iter()
+            .zip(row)
+            .map(|((name, _), cell)| (name.to_string(), cell.to_json()))
+            .collect::<Map<String, Value>>();
+        lines.push_str(&serde_json::to_string(&object)?);
+        lines.push('\n');
+    }
+    std::fs::write(file_path, lines)?;
+    Ok(())
+}
+
+fn write_parquet(table: &Table, file_path: &Path) -> Result<()> {
+    let fields = table
+        .columns
+        .iter()
+        .map(|(name, column_type)| {
+            let data_type = match column_type {
+                ColumnType::Text => DataType::Utf8,
+                ColumnType::Int => DataType::Int64,
+                ColumnType::Bool => DataType::Boolean,
+            };
+            Field::new(*name, data_type, true)
+        })
+        .collect::<Vec<Field>>();
+    let arrays = table
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, (_, column_type))| {
+            let cells = table.rows.iter().map(|row| &row[i]);
+            let array: ArrayRef = match column_type {
+                ColumnType::Text => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Text(text) => Some(text.as_str()),
+                            _ => None,
+                        })
+                        .collect::<StringArray>(),
+                ),
+                ColumnType::Int => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Int(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect::<Int64Array>(),
+                ),
+                ColumnType::Bool => Arc::new(
+                    cells
+                        .map(|cell| match cell {
+                            Cell::Bool(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect::<BooleanArray>(),
+                ),
+            };
+            array
+        })
+        .collect::<Vec<ArrayRef>>();
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .build();
+    let mut writer = ArrowWriter::try_new(
+        std::fs::File::create(file_path)?,
+        batch.schema(),
+        Some(properties),
+    )?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Write each of `tables` flattened from `sessions` to `<output_dir>/<table>.<format>`.
+///
+/// With `columns`, only those columns are written, in the given order, from the tables that have
+/// one of them besides the [`KEY_COLUMNS`], e.g. only the sessions table for `year,title`. When only
+/// key columns are selected, the first of `tables` is written. Returns the written files and their
+/// row counts.
+pub fn export_sessions(
+    sessions: &[Session],
+    tables: &[ExportTable],
+    columns: &[String],
+    format: ExportFormat,
+    output_dir: &Path,
+) -> Result<Vec<(PathBuf, usize)>> {
+    let mut built = tables
+        .iter()
+        .map(|table| (*table, build_table(*table, sessions)))
+        .collect::<Vec<(ExportTable, Table)>>();
+    if !columns.is_empty() {
+        if let Some(unknown) = columns
+            .iter()
+            .find(|column| !built.iter().any(|(_, table)| table.has_column(column)))
+        {
+            return Err(anyhow!("Unknown column: {}", unknown));
+        }
+        if built.iter().any(|(_, table)| table.is_selected_by(columns)) {
+            built.retain(|(_, table)| table.is_selected_by(columns));
+        } else {
+            built.truncate(1);
+        }
+        built = built
+            .into_iter()
+            .map(|(name, table)| (name, table.select(columns)))
+            .collect();
+    }
+
+    std::fs::create_dir_all(output_dir)?;
+    let mut written = Vec::new();
+    for (table_name, table) in built {
+        let file_path = output_dir.join(format!("{}.{}", table_name.name(), format.extension()));
+        match format {
+            ExportFormat::Csv => write_csv(&table, &file_path)?,
+            ExportFormat::Jsonl => write_jsonl(&table, &file_path)?,
+            ExportFormat::Parquet => write_parquet(&table, &file_path)?,
+        }
+        written.push((file_path, table.rows.len()));
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::models::{Affiliation, Author};
+
+    fn written_tables(columns: &[&str]) -> Vec<(String, Vec<String>)> {
+        let mut session = Session {
+            id: "1A1-GS-1-01".to_string(),
+            title: "title".to_string(),
+            keywords: vec!["keyword".to_string()],
+            ..Default::default()
+        };
+        session.authors = vec![Author::new(
+            "山田 太郎".to_string(),
+            vec![Affiliation::new("東京大学".to_string(), None)],
+        )];
+        let output_dir = std::env::temp_dir().join(format!(
+            "jsai-export-{}-{}",
+            std::process::id(),
+            columns.join("-")
+        ));
+        let columns = columns
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>();
+        let written = export_sessions(
+            &[session],
+            &[
+                ExportTable::Sessions,
+                ExportTable::Authors,
+                ExportTable::Keywords,
+            ],
+            &columns,
+            ExportFormat::Csv,
+            &output_dir,
+        )
+        .unwrap();
+        let tables = written
+            .iter()
+            .map(|(file_path, _)| {
+                let mut reader = csv::Reader::from_path(file_path).unwrap();
+                let header = reader.headers().unwrap().iter().map(String::from).collect();
+                let name = file_path.file_stem().unwrap().to_string_lossy().to_string();
+                (name, header)
+            })
+            .collect();
+        std::fs::remove_dir_all(output_dir).unwrap();
+        tables
+    }
+
+    #[test]
+    fn key_columns_alone_do_not_select_a_table() {
+        assert_eq!(
+            written_tables(&["year", "title"]),
+            vec![(
+                "sessions".to_string(),
+                vec!["year".to_string(), "title".to_string()]
+            )]
+        );
+        assert_eq!(
+            written_tables(&["session_id", "name", "keyword"])
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<String>>(),
+            vec!["authors", "keywords"]
+        );
+        assert_eq!(
+            written_tables(&["session_id", "url"]),
+            vec![(
+                "sessions".to_string(),
+                vec!["session_id".to_string(), "url".to_string()]
+            )]
+        );
+    }
+}
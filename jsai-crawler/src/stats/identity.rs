@@ -0,0 +1,501 @@
+use crate::stats::authors::normalize_for_match;
+use crate::stats::models::Dataset;
+use anyhow::Result;
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+/// Minimum affiliation similarity for two occurrences of the same name to be the same person.
+const AFFILIATION_SIMILARITY_THRESHOLD: f64 = 0.5;
+
+fn is_latin(text: &str) -> bool {
+    text.chars()
+        .filter(|c| c.is_alphabetic())
+        .all(|c| c.is_ascii_alphabetic())
+}
+
+/// Normalize an author name for display: NFKC, single spaces, and family name first.
+///
+/// Japanese names are kept in their order. Latin names are reordered from `Given Family` to
+/// `Family Given`, unless the family name is marked by a comma (`Yamada, Taro`) or upper case (`YAMADA Taro`).
+pub fn normalize_author_name(name: &str) -> String {
+    let name: String = name.nfkc().collect();
+    let name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if !is_latin(&name) {
+        return name;
+    }
+
+    if let Some((family, given)) = name.split_once(',') {
+        return format!("{} {}", family.trim(), given.trim());
+    }
+    let tokens = name.split(' ').collect::<Vec<&str>>();
+    if tokens.len() < 2 {
+        return name;
+    }
+    let is_upper = |t: &&str| t.chars().count() > 1 && t.chars().all(|c| !c.is_lowercase());
+    if let Some(pos) = tokens.iter().position(is_upper) {
+        let mut ordered = vec![tokens[pos]];
+        ordered.extend(
+            tokens
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != pos)
+                .map(|(_, t)| *t),
+        );
+        return ordered.join(" ");
+    }
+    let mut ordered = vec![*tokens.last().unwrap()];
+    ordered.extend(&tokens[..tokens.len() - 1]);
+    ordered.join(" ")
+}
+
+/// Key under which spellings of the same name match: normalized, family name first, case-folded
+/// and without spaces, e.g. `yamadataro` for `Taro Yamada` and `YAMADA Taro`.
+pub fn name_key(name: &str) -> String {
+    normalize_for_match(&normalize_author_name(name).replace(',', ""))
+}
+
+/// [`name_key`] of a romanized name, which is stored family name first and so is not reordered;
+/// `Yamada Taro`, the romanized form of `山田 太郎`, meets `Taro Yamada` under `yamadataro`.
+pub fn romanized_key(romanized: &str) -> String {
+    normalize_for_match(&romanized.replace(',', ""))
+}
+
+fn bigrams(text: &str) -> FxHashSet<(char, char)> {
+    let chars = text.chars().collect::<Vec<char>>();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// Dice coefficient of the character bigrams of two affiliations.
+///
+/// An unknown (empty) affiliation matches nothing: letting it match anything would chain the
+/// people of one name at different affiliations into one cluster.
+pub fn affiliation_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_for_match(a), normalize_for_match(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a.contains(&b) || b.contains(&a) {
+        return 1.0;
+    }
+    let (a, b) = (bigrams(&a), bigrams(&b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+/// Manually assigned author id, read from a CSV file with the header `name,affiliation,author_id`.
+/// An empty affiliation applies the override to every occurrence of the name. The id is given to
+/// the whole cluster of the matched occurrences.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuthorOverride {
+    pub name: String,
+    #[serde(default)]
+    pub affiliation: String,
+    pub author_id: String,
+}
+
+pub fn load_overrides(file_path: &Path) -> Result<Vec<AuthorOverride>> {
+    let mut reader = csv::Reader::from_path(file_path)?;
+    Ok(reader
+        .deserialize()
+        .collect::<std::result::Result<Vec<AuthorOverride>, csv::Error>>()?)
+}
+
+/// A resolved author with every spelling and affiliation seen across the datasets.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthorIdentity {
+    pub author_id: String,
+    pub name: String,
+    pub names: Vec<String>,
+    pub affiliations: Vec<String>,
+    pub years: Vec<u32>,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Occurrence {
+    dataset: usize,
+    session: usize,
+    author: usize,
+    year: u32,
+    url: String,
+    name: String,
+    romanized: Option<String>,
+    affiliation: String,
+}
+
+fn find(parents: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    parents[i] = root;
+    root
+}
+
+/// Merge the clusters of `i` and `j`, unless overrides assign them different ids.
+///
+/// The earliest occurrence stays the root, and the root carries the override id of the cluster.
+fn union(parents: &mut [usize], labels: &mut [Option<String>], i: usize, j: usize) {
+    let (ri, rj) = (find(parents, i), find(parents, j));
+    if ri == rj {
+        return;
+    }
+    if let (Some(a), Some(b)) = (&labels[ri], &labels[rj])
+        && a != b
+    {
+        return;
+    }
+    let (root, child) = (ri.min(rj), ri.max(rj));
+    parents[child] = root;
+    if labels[root].is_none() {
+        labels[root] = labels[child].take();
+    }
+}
+
+fn stable_id(key: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(key.as_bytes()));
+    format!("a-{}", &digest[..12])
+}
+
+fn apply_override(overrides: &[AuthorOverride], occurrence: &Occurrence) -> Option<String> {
+    let key = name_key(&occurrence.name);
+    overrides
+        .iter()
+        .filter(|o| name_key(&o.name) == key)
+        .find(|o| {
+            o.affiliation.is_empty()
+                || normalize_for_match(&o.affiliation)
+                    == normalize_for_match(&occurrence.affiliation)
+        })
+        .map(|o| o.author_id.clone())
+}
+
+/// Assign `author_id` to every author of the datasets.
+///
+/// Occurrences with the same [`name_key`] and similar affiliations are the same person; an
+/// occurrence without an affiliation stays on its own unless an override assigns it. The id is
+/// derived from the name and the affiliation of the earliest occurrence, so that it stays stable
+/// when later years are added. Overrides take precedence over the similarity rule: occurrences
+/// given the same id form one cluster, clusters given different ids are never merged, and every
+/// member of a cluster gets its override id.
+pub fn resolve_author_ids(
+    datasets: &mut [Dataset],
+    overrides: &[AuthorOverride],
+) -> Vec<AuthorIdentity> {
+    let mut occurrences = Vec::new();
+    for (d, dataset) in datasets.iter().enumerate() {
+        for (s, session) in dataset.sessions.iter().enumerate() {
+            for (a, author) in session.authors.iter().enumerate() {
+                occurrences.push(Occurrence {
+                    dataset: d,
+                    session: s,
+                    author: a,
+                    year: session.year,
+                    url: session.url.clone(),
+                    name: author.name.clone(),
+                    romanized: author.romanized.clone(),
+                    affiliation: author.affiliation(),
+                });
+            }
+        }
+    }
+    occurrences.sort_by(|a, b| (a.year, &a.url, a.author).cmp(&(b.year, &b.url, b.author)));
+
+    // overrides first, so that the similarity rule cannot merge clusters they keep apart
+    let mut parents = (0..occurrences.len()).collect::<Vec<usize>>();
+    let mut labels = occurrences
+        .iter()
+        .map(|occurrence| apply_override(overrides, occurrence))
+        .collect::<Vec<Option<String>>>();
+    let mut first_with_id: FxHashMap<String, usize> = FxHashMap::default();
+    for i in 0..occurrences.len() {
+        if let Some(id) = labels[i].clone() {
+            let first = *first_with_id.entry(id).or_insert(i);
+            union(&mut parents, &mut labels, first, i);
+        }
+    }
+
+    // cluster the occurrences of each name by affiliation similarity
+    let mut by_name: FxHashMap<String, Vec<usize>> = FxHashMap::default();
+    for (i, occurrence) in occurrences.iter().enumerate() {
+        let key = name_key(&occurrence.name);
+        // a Japanese name also meets its romanized spelling, e.g. `山田 太郎` and `Taro Yamada`
+        if let Some(romanized) = &occurrence.romanized
+            && romanized_key(romanized) != key
+        {
+            by_name.entry(romanized_key(romanized)).or_default().push(i);
+        }
+        by_name.entry(key).or_default().push(i);
+    }
+    for members in by_name.values() {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[..n] {
+                let similarity = affiliation_similarity(
+                    &occurrences[i].affiliation,
+                    &occurrences[j].affiliation,
+                );
+                if similarity >= AFFILIATION_SIMILARITY_THRESHOLD {
+                    union(&mut parents, &mut labels, i, j);
+                }
+            }
+        }
+    }
+
+    // the root of a cluster is its earliest occurrence
+    let mut ids = Vec::with_capacity(occurrences.len());
+    for i in 0..occurrences.len() {
+        let root = find(&mut parents, i);
+        let id = match &labels[root] {
+            Some(id) => id.clone(),
+            None => stable_id(&format!(
+                "{}|{}",
+                name_key(&occurrences[root].name),
+                normalize_for_match(&occurrences[root].affiliation)
+            )),
+        };
+        ids.push(id);
+    }
+
+    let mut identities: FxHashMap<String, AuthorIdentity> = FxHashMap::default();
+    let mut sessions: FxHashMap<String, FxHashSet<String>> = FxHashMap::default();
+    for (occurrence, id) in occurrences.iter().zip(&ids) {
+        datasets[occurrence.dataset].sessions[occurrence.session].authors[occurrence.author]
+            .author_id = Some(id.clone());
+
+        let identity = identities
+            .entry(id.clone())
+            .or_insert_with(|| AuthorIdentity {
+                author_id: id.clone(),
+                name: normalize_author_name(&occurrence.name),
+                ..Default::default()
+            });
+        let name = normalize_author_name(&occurrence.name);
+        if !identity.names.contains(&name) {
+            identity.names.push(name);
+        }
+        if !occurrence.affiliation.is_empty()
+            && !identity.affiliations.contains(&occurrence.affiliation)
+        {
+            identity.affiliations.push(occurrence.affiliation.clone());
+        }
+        if !identity.years.contains(&occurrence.year) {
+            identity.years.push(occurrence.year);
+        }
+        sessions
+            .entry(id.clone())
+            .or_default()
+            .insert(occurrence.url.clone());
+    }
+
+    let mut identities = identities
+        .into_values()
+        .map(|mut identity| {
+            identity.sessions = sessions[&identity.author_id].len();
+            identity
+        })
+        .collect::<Vec<AuthorIdentity>>();
+    identities.sort_by(|a, b| a.author_id.cmp(&b.author_id));
+    identities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::models::{Affiliation, Author, Section, Session};
+
+    fn session(year: u32, number: usize, authors: Vec<Author>) -> Session {
+        Session::new(
+            "jsai".to_string(),
+            year,
+            format!("1A1-GS-1-{:02}", number),
+            "title".to_string(),
+            format!(
+                "https://confit.atlas.jp/guide/event/jsai{}/subject/1A1-GS-1-{:02}/tables",
+                year, number
+            ),
+            String::new(),
+            String::new(),
+            authors,
+            String::new(),
+            Vec::new(),
+            Section::default(),
+        )
+    }
+
+    fn author(name: &str, affiliation: &str) -> Author {
+        let affiliations = if affiliation.is_empty() {
+            Vec::new()
+        } else {
+            vec![Affiliation::new(affiliation.to_string(), None)]
+        };
+        Author::new(name.to_string(), affiliations)
+    }
+
+    fn resolved_ids(sessions: Vec<Session>) -> Vec<String> {
+        resolved_ids_with(sessions, &[])
+    }
+
+    fn resolved_ids_with(sessions: Vec<Session>, overrides: &[AuthorOverride]) -> Vec<String> {
+        let mut datasets = vec![Dataset::new(Vec::new(), sessions)];
+        resolve_author_ids(&mut datasets, overrides);
+        datasets[0]
+            .sessions
+            .iter()
+            .flat_map(|s| s.authors.iter())
+            .map(|a| a.author_id.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn name_key_ignores_spacing_and_width_of_japanese_names() {
+        assert_eq!(name_key("山田 太郎"), "山田太郎");
+        assert_eq!(name_key("山田　太郎"), "山田太郎");
+        assert_eq!(name_key("山田太郎"), "山田太郎");
+    }
+
+    #[test]
+    fn name_key_puts_the_family_name_of_latin_names_first() {
+        assert_eq!(name_key("Taro Yamada"), "yamadataro");
+        assert_eq!(name_key("YAMADA Taro"), "yamadataro");
+        assert_eq!(name_key("Yamada, Taro"), "yamadataro");
+        assert_eq!(name_key("Ｔａｒｏ　Ｙａｍａｄａ"), "yamadataro");
+    }
+
+    #[test]
+    fn romanized_key_matches_the_latin_spelling() {
+        assert_eq!(romanized_key("Yamada Taro"), name_key("Taro Yamada"));
+        assert_ne!(name_key("Yamada Taro"), name_key("Taro Yamada"));
+    }
+
+    #[test]
+    fn japanese_name_meets_its_latin_spelling() {
+        let mut japanese = author("山田 太郎", "東京大学");
+        japanese.reading = Some("ヤマダ タロウ".to_string());
+        let mut authors = vec![japanese, author("Taro Yamada", "東京大学")];
+        crate::stats::authors::fill_name_readings(&mut authors);
+        assert_eq!(authors[0].romanized.as_deref(), Some("Yamada Taro"));
+        assert_eq!(authors[1].romanized.as_deref(), Some("Yamada Taro"));
+
+        let latin = authors.pop().unwrap();
+        let ids = resolved_ids(vec![
+            session(2023, 1, authors),
+            session(2024, 1, vec![latin]),
+        ]);
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn empty_affiliations_do_not_match() {
+        assert_eq!(affiliation_similarity("", "東京大学"), 0.0);
+        assert_eq!(affiliation_similarity("東京大学", ""), 0.0);
+        assert_eq!(affiliation_similarity("東京大学", "東京大学大学院"), 1.0);
+    }
+
+    #[test]
+    fn unknown_affiliation_does_not_chain_different_people() {
+        let ids = resolved_ids(vec![
+            session(2023, 1, vec![author("山田 太郎", "東京大学")]),
+            session(2024, 1, vec![author("山田 太郎", "")]),
+            session(2025, 1, vec![author("山田 太郎", "九州工業大学")]),
+        ]);
+        assert_ne!(ids[0], ids[2]);
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[1], ids[2]);
+    }
+
+    #[test]
+    fn same_name_and_affiliation_are_linked_across_years() {
+        let ids = resolved_ids(vec![
+            session(2023, 1, vec![author("山田 太郎", "東京大学")]),
+            session(
+                2024,
+                1,
+                vec![author("山田　太郎", "東京大学大学院情報理工学系研究科")],
+            ),
+            session(2025, 1, vec![author("山田 太郎", "京都大学")]),
+        ]);
+        assert_eq!(ids[0], ids[1]);
+        assert_ne!(ids[0], ids[2]);
+    }
+
+    #[test]
+    fn year_comes_from_the_session() {
+        let mut sessions = vec![
+            session(2024, 1, vec![author("佐藤 花子", "理化学研究所")]),
+            session(2023, 1, vec![author("佐藤 花子", "理化学研究所")]),
+        ];
+        sessions[0].url = "https://example.com/b".to_string();
+        sessions[1].url = "https://example.com/a".to_string();
+        let mut datasets = vec![Dataset::new(Vec::new(), sessions)];
+        let identities = resolve_author_ids(&mut datasets, &[]);
+        assert_eq!(identities.len(), 1);
+        assert_eq!(identities[0].years, vec![2023, 2024]);
+    }
+
+    fn override_of(name: &str, affiliation: &str, author_id: &str) -> AuthorOverride {
+        AuthorOverride {
+            name: name.to_string(),
+            affiliation: affiliation.to_string(),
+            author_id: author_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn override_relabels_the_whole_cluster() {
+        let ids = resolved_ids_with(
+            vec![
+                session(2023, 1, vec![author("山田 太郎", "東京大学")]),
+                session(
+                    2024,
+                    1,
+                    vec![author("山田 太郎", "東京大学大学院情報理工学系研究科")],
+                ),
+                session(2025, 1, vec![author("山田 太郎", "東京大学")]),
+            ],
+            &[override_of(
+                "山田 太郎",
+                "東京大学大学院情報理工学系研究科",
+                "yamada",
+            )],
+        );
+        assert_eq!(ids, vec!["yamada", "yamada", "yamada"]);
+    }
+
+    #[test]
+    fn overrides_merge_and_split_clusters() {
+        let sessions = || {
+            vec![
+                session(2023, 1, vec![author("山田 太郎", "東京大学")]),
+                session(2024, 1, vec![author("山田 太郎", "東京大学")]),
+                session(2025, 1, vec![author("山田 太郎", "京都大学")]),
+            ]
+        };
+        let ids = resolved_ids_with(
+            sessions(),
+            &[
+                override_of("山田 太郎", "東京大学", "yamada"),
+                override_of("山田 太郎", "京都大学", "yamada"),
+            ],
+        );
+        assert_eq!(ids, vec!["yamada", "yamada", "yamada"]);
+
+        let mut sessions = sessions();
+        sessions[1].authors[0].affiliations[0].name = "東京大学病院".to_string();
+        let ids = resolved_ids_with(
+            sessions,
+            &[
+                override_of("山田 太郎", "東京大学", "yamada-1"),
+                override_of("山田 太郎", "東京大学病院", "yamada-2"),
+            ],
+        );
+        assert_eq!(ids[0], "yamada-1");
+        assert_eq!(ids[1], "yamada-2");
+        assert_ne!(ids[2], ids[0]);
+        assert_ne!(ids[2], ids[1]);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::stats::models::{Dataset, Session, Year};
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Magic number at the start of every zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+fn is_dataset_file(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (name.ends_with(".json") || name.ends_with(".json.zst")) && !name.contains(".corrections.json")
+}
+
+/// Dataset files of the given paths; a directory stands for the `.json` and `.json.zst` files in it,
+/// except corrections files.
+pub fn dataset_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = std::fs::read_dir(path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<PathBuf>>>()?;
+            entries.retain(|entry| entry.is_file() && is_dataset_file(entry));
+            entries.sort();
+            if entries.is_empty() {
+                return Err(anyhow!("No dataset files in {}", path.display()));
+            }
+            files.extend(entries);
+        } else if path.exists() {
+            files.push(path.clone());
+        } else {
+            return Err(anyhow!("No such file or directory: {}", path.display()));
+        }
+    }
+    Ok(files)
+}
+
+/// Read a JSON file, decompressing it first when it is zstd-compressed, whatever its extension.
+pub fn read_json(file_path: &Path) -> Result<Value> {
+    let mut data = Vec::new();
+    std::fs::File::open(file_path)?.read_to_end(&mut data)?;
+    if data.starts_with(&ZSTD_MAGIC) {
+        data = zstd::decode_all(data.as_slice())?;
+    }
+    serde_json::from_slice(&data)
+        .map_err(|e| anyhow!("{} is not a JSON dataset: {}", file_path.display(), e))
+}
+
+/// Check that `value` has the layout of a crawl, so that other JSON files fail with a clear message.
+pub fn check_dataset_format(value: &Value, file_path: &Path) -> Result<()> {
+    let has_array = |key: &str| value.get(key).is_some_and(Value::is_array);
+    if has_array("sections") && has_array("sessions") {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Unknown format of {}: expected a crawl dataset with `sections` and `sessions`",
+            file_path.display()
+        ))
+    }
+}
+
+/// Load the sessions of every dataset in `paths` with their corrections applied.
+///
+/// Progress goes to stderr, so that commands writing their result to stdout can be piped.
+pub fn load_sessions(paths: &[PathBuf]) -> Result<Vec<Session>> {
+    let mut sessions = Vec::new();
+    for file_path in dataset_files(paths)? {
+        let loaded = Dataset::load_sessions(&file_path)?;
+        eprintln!(
+            "Loaded {} sessions from {}",
+            loaded.len(),
+            file_path.display()
+        );
+        sessions.extend(loaded);
+    }
+    Ok(sessions)
+}
+
+/// Load the sessions of every dataset in `paths` with their corrections applied, grouped by year.
+/// The year of each session is read from the dataset itself, see `stats::migrations`.
+pub fn load_sessions_by_year(paths: &[PathBuf]) -> Result<BTreeMap<Year, Vec<Session>>> {
+    let mut sessions_by_year: BTreeMap<Year, Vec<Session>> = BTreeMap::new();
+    for session in load_sessions(paths)? {
+        sessions_by_year
+            .entry(session.year)
+            .or_default()
+            .push(session);
+    }
+    Ok(sessions_by_year)
+}
@@ -0,0 +1,84 @@
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::path::Path;
+
+/// Version of the dataset layout written by this build. Bump it together with a new migration.
+pub const SCHEMA_VERSION: u32 = 1;
+
+static EVENT_URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"/event/(?P<event>[a-z]+)(?P<year>\d{4})/").unwrap());
+static EVENT_FILE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<event>[a-z]+)(?P<year>\d{4})").unwrap());
+
+/// Migration from the version at its index to the next one.
+type Migration = fn(&mut Value, &Path) -> Result<()>;
+
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [add_event_and_year];
+
+/// Event and year of a session, e.g. `("jsai", 2024)`, from a Confit URL such as
+/// `https://confit.atlas.jp/guide/event/jsai2024/subject/...` or a file name such as `jsai2024.json`.
+pub fn parse_event(text: &str) -> Option<(String, u32)> {
+    EVENT_URL_REGEX
+        .captures(text)
+        .or_else(|| EVENT_FILE_REGEX.captures(text))
+        .and_then(|caps| Some((caps["event"].to_string(), caps["year"].parse().ok()?)))
+}
+
+/// 0 → 1: the crawlers of each year wrote sessions without `event` and `year`.
+fn add_event_and_year(dataset: &mut Value, file_path: &Path) -> Result<()> {
+    let from_file_name = file_path
+        .file_name()
+        .and_then(|name| parse_event(&name.to_string_lossy()));
+    let sessions = dataset
+        .get_mut("sessions")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| anyhow!("No sessions in {}", file_path.display()))?;
+    for session in sessions.iter_mut().filter_map(Value::as_object_mut) {
+        let url = session
+            .get("url")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let (event, year) = parse_event(&url)
+            .or_else(|| from_file_name.clone())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Cannot tell the event of session {} in {}",
+                    url,
+                    file_path.display()
+                )
+            })?;
+        session.entry("event").or_insert(Value::from(event));
+        session.entry("year").or_insert(Value::from(year));
+    }
+    Ok(())
+}
+
+/// Bring a dataset read from `file_path` up to `SCHEMA_VERSION`. Files without `schema_version`
+/// are version 0, the layout of the per-year crawlers.
+pub fn migrate(dataset: &mut Value, file_path: &Path) -> Result<()> {
+    let version = match dataset.get("schema_version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| anyhow!("Invalid schema_version in {}", file_path.display()))?
+            as u32,
+        None => 0,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "{} has schema version {}, newer than the supported version {}",
+            file_path.display(),
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(dataset, file_path)?;
+    }
+    if let Some(dataset) = dataset.as_object_mut() {
+        dataset.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    }
+    Ok(())
+}
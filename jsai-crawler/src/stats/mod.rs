@@ -1,2 +1,16 @@
+pub mod affiliations;
 pub mod ai;
+pub mod audit;
+pub mod authors;
+pub mod bibliography;
+pub mod corrections;
+pub mod db;
+pub mod eval;
+pub mod export;
+pub mod identity;
+pub mod loader;
+pub mod migrations;
 pub mod models;
+pub mod researchers;
+pub mod tracks;
+pub mod validate;
@@ -1,12 +1,24 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use crate::llm::prompt::PromptTemplate;
 use crate::mecab::generate_wordcloud_input;
+use crate::stats::affiliations::{
+    AffiliationDictionary, AffiliationLevel, OrgType, count_organizations,
+};
+use crate::stats::corrections::apply_corrections_for;
+use crate::stats::loader::{check_dataset_format, read_json};
+use crate::stats::migrations::{SCHEMA_VERSION, migrate};
+use crate::stats::tracks::{SessionId, TrackType};
 use anyhow::Result;
 use derive_new::new;
 use fxhash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
-type Year = u32;
+pub type Year = u32;
+
+/// Number of most frequent organizations reported by `Stats::analyze`.
+const TOP_ORGANIZATIONS: usize = 20;
 
 #[derive(Debug, Clone, Default, new, Serialize, Deserialize)]
 pub struct Section {
@@ -16,14 +28,151 @@ pub struct Section {
     pub time: String,
 }
 
+/// One affiliation of an author, with its index number in the Confit author block when it has one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, new, Serialize, Deserialize)]
+pub struct Affiliation {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AffiliationRepr {
+    Name(String),
+    Full(Affiliation),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AffiliationsRepr {
+    /// `"affiliation": "東京大学"` of the files written before authors had several affiliations
+    Single(String),
+    List(Vec<AffiliationRepr>),
+}
+
+/// Deserialize `affiliations` from a list of affiliations or names, or from the former single
+/// `affiliation` string.
+pub fn deserialize_affiliations<'de, D>(deserializer: D) -> Result<Vec<Affiliation>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let affiliations = match AffiliationsRepr::deserialize(deserializer)? {
+        AffiliationsRepr::Single(name) if name.trim().is_empty() => Vec::new(),
+        AffiliationsRepr::Single(name) => vec![Affiliation::new(name, None)],
+        AffiliationsRepr::List(list) => list
+            .into_iter()
+            .map(|affiliation| match affiliation {
+                AffiliationRepr::Name(name) => Affiliation::new(name, None),
+                AffiliationRepr::Full(affiliation) => affiliation,
+            })
+            .collect(),
+    };
+    Ok(affiliations)
+}
+
 #[derive(Debug, Clone, Default, new, Serialize, Deserialize)]
 pub struct Author {
     pub name: String,
-    pub affiliation: String,
+    /// Affiliations in the order they are listed for the author
+    #[serde(
+        default,
+        alias = "affiliation",
+        deserialize_with = "deserialize_affiliations"
+    )]
+    pub affiliations: Vec<Affiliation>,
+    /// Stable id across years, assigned by the `resolve-authors` command
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<String>,
+    /// 1-based position in the author list
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<u32>,
+    /// Marked with `○` as the presenting author
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_presenter: bool,
+    /// Presenter marked as a student, with `◎` or `(学生)`
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_student_presenter: bool,
+    /// Reading of the name in katakana, e.g. `ヤマダ タロウ`
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading: Option<String>,
+    /// Name in Latin script, family name first, e.g. `Yamada Taro`
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub romanized: Option<String>,
+    /// ORCID iD, e.g. `0000-0002-1825-0097`, linked by the `link-ids` command
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orcid: Option<String>,
+    /// researchmap permalink, linked by the `link-ids` command
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub researchmap_id: Option<String>,
+}
+
+impl Author {
+    /// All affiliations joined by `", "`, for matching and display.
+    pub fn affiliation(&self) -> String {
+        self.affiliations
+            .iter()
+            .map(|affiliation| affiliation.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ")
+    }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorParser {
+    #[default]
+    Rules,
+    Llm,
+}
+
+/// How the `authors` of a session were produced, so that a dataset can tell which prompt and model made them.
+#[derive(Debug, Clone, Default, new, Serialize, Deserialize)]
+pub struct AuthorProvenance {
+    pub parser: AuthorParser,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+impl AuthorProvenance {
+    pub fn rules() -> Self {
+        Self::default()
+    }
+
+    pub fn llm(prompt: &PromptTemplate, model_id: &str) -> Self {
+        Self {
+            parser: AuthorParser::Llm,
+            prompt_id: Some(prompt.id.clone()),
+            prompt_version: Some(prompt.version.clone()),
+            model_id: Some(model_id.to_string()),
+            temperature: Some(prompt.temperature),
+        }
+    }
+}
+
+/// A session of any year. The event and year come from the crawler, or from the session URL
+/// for datasets written before they were recorded.
 #[derive(Debug, Clone, Default, new, Serialize, Deserialize)]
 pub struct Session {
+    /// Confit event name, e.g. `jsai`
+    #[serde(default)]
+    pub event: String,
+    #[serde(default)]
+    pub year: Year,
     pub id: String,
     pub title: String,
     pub url: String,
@@ -31,14 +180,80 @@ pub struct Session {
     #[serde(rename = "abstract")]
     pub abstract_text: String,
     pub authors: Vec<Author>,
+    /// The author block as shown on the session page
+    #[serde(default)]
+    pub raw_authors: String,
     pub keywords: Vec<String>,
     pub section: Section,
+    /// Reasons why the parsed authors need a human review
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_issues: Vec<String>,
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_provenance: Option<AuthorProvenance>,
 }
 
 impl Session {
     pub fn title_with_id(&self) -> String {
         format!("[{}] {}", self.id, self.title)
     }
+
+    pub fn parsed_id(&self) -> Result<SessionId> {
+        self.id.parse()
+    }
+
+    /// Track type of the session, or `None` when its id cannot be parsed.
+    pub fn track_type(&self) -> Option<TrackType> {
+        self.parsed_id().ok().map(|id| id.track_type)
+    }
+}
+
+/// Layout of the JSON files written by the crawlers, which is the same for every year.
+#[derive(Debug, Clone, Default, new, Serialize, Deserialize)]
+pub struct Dataset {
+    /// Layout version of the file, see `stats::migrations`
+    #[new(value = "SCHEMA_VERSION")]
+    #[serde(default)]
+    pub schema_version: u32,
+    pub sections: Vec<Section>,
+    pub sessions: Vec<Session>,
+    /// Section list pages whose next page did not load; the sections after them are missing
+    #[new(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unloaded_pages: Vec<String>,
+}
+
+impl Dataset {
+    /// Read a dataset of any schema version, plain or zstd-compressed, migrating it to the current one.
+    pub fn load(file_path: &Path) -> Result<Self> {
+        let mut value = read_json(file_path)?;
+        check_dataset_format(&value, file_path)?;
+        migrate(&mut value, file_path)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Sessions of the dataset at `file_path` with its corrections file applied.
+    pub fn load_sessions(file_path: &Path) -> Result<Vec<Session>> {
+        let mut sessions = Self::load(file_path)?.sessions;
+        apply_corrections_for(file_path, &mut sessions)?;
+        Ok(sessions)
+    }
+
+    /// Write the dataset through a temporary file so that an interrupted run keeps the previous file intact.
+    /// A `.zst` file is written zstd-compressed.
+    pub fn save(&self, file_path: &Path) -> Result<()> {
+        let mut tmp_path = file_path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let data = serde_json::to_vec(self)?;
+        if file_path.extension().is_some_and(|ext| ext == "zst") {
+            std::fs::write(&tmp_path, zstd::encode_all(data.as_slice(), 0)?)?;
+        } else {
+            std::fs::write(&tmp_path, data)?;
+        }
+        std::fs::rename(tmp_path, file_path)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, new)]
@@ -102,19 +317,14 @@ impl Stats {
         year: Year,
         sessions: Vec<Session>,
         output_dir: PathBuf,
+        affiliations: &AffiliationDictionary,
+        affiliation_level: AffiliationLevel,
     ) -> Result<()> {
         self.items.insert(year, Vec::new());
-        // Filter sessions to include only those that are relevant
+        // Filter sessions to include only research presentations, see `TrackType::PRESENTATIONS`
         let sessions: Vec<Session> = sessions
             .iter()
-            .filter(|s| {
-                s.id.contains("GS") // General Sessions
-                    || s.id.contains("-OS-")    // Organized Sessions
-                    || s.id.contains("-Win-")   // Poster Sessions
-                    || s.id.contains("-PS-")    // Keynote Sessions
-                    || s.id.contains("-SS-")    // Student Sessions
-                    || s.id.contains("-KS-") // Kikaku Sessions
-            })
+            .filter(|s| s.track_type().is_some_and(|t| t.is_presentation()))
             .cloned()
             .collect();
 
@@ -132,6 +342,34 @@ impl Stats {
                 ));
         }
 
+        // ====== Number of sessions by track type ======
+        {
+            let mut by_track: BTreeMap<TrackType, Vec<String>> = BTreeMap::new();
+            for session in &sessions {
+                if let Some(track_type) = session.track_type() {
+                    by_track
+                        .entry(track_type)
+                        .or_default()
+                        .push(session.title_with_id());
+                }
+            }
+            for (track_type, titles) in by_track {
+                self.items
+                    .get_mut(&year)
+                    .unwrap_or(&mut Vec::new())
+                    .push(StatsItem::new(
+                        format!("{} Sessions", track_type.label()),
+                        titles.len() as f64,
+                        format!(
+                            "Number of sessions of the {} track type",
+                            track_type.label()
+                        ),
+                        year,
+                        titles,
+                    ));
+            }
+        }
+
         // ====== Generate wordcloud text input ======
         {
             // Tokenize the abstracts and titles for word cloud generation
@@ -172,6 +410,88 @@ impl Stats {
             year,
         )?;
 
+        // ====== Organization analysis ======
+        for (presenters_only, name, whom) in [
+            (false, "Sessions", "an author"),
+            (true, "Presenter Sessions", "the presenter"),
+        ] {
+            let organizations = count_organizations(
+                affiliations,
+                &sessions,
+                presenters_only,
+                AffiliationLevel::Organization,
+            );
+            for org_type in [
+                OrgType::University,
+                OrgType::Company,
+                OrgType::NationalInstitute,
+                OrgType::Other,
+            ] {
+                let mut titles = organizations
+                    .iter()
+                    .filter(|(organization, _)| organization.org_type == org_type)
+                    .flat_map(|(_, titles)| titles.iter().cloned())
+                    .collect::<Vec<String>>();
+                titles.sort();
+                titles.dedup();
+                self.items
+                    .get_mut(&year)
+                    .unwrap_or(&mut Vec::new())
+                    .push(StatsItem::new(
+                        format!("{} {}", org_type.label(), name),
+                        titles.len() as f64,
+                        format!(
+                            "Number of sessions with {} affiliated with a {} organization",
+                            whom,
+                            org_type.label().to_lowercase()
+                        ),
+                        year,
+                        titles,
+                    ));
+            }
+            if presenters_only {
+                continue;
+            }
+            // the most frequent organizations, units or labs
+            let organizations =
+                count_organizations(affiliations, &sessions, false, affiliation_level);
+            for (organization, titles) in organizations.iter().take(TOP_ORGANIZATIONS) {
+                self.items
+                    .get_mut(&year)
+                    .unwrap_or(&mut Vec::new())
+                    .push(StatsItem::new(
+                        format!("{:?}: {}", affiliation_level, organization.name),
+                        titles.len() as f64,
+                        format!(
+                            "Number of sessions with an author affiliated with {} ({})",
+                            organization.name,
+                            organization.org_type.label()
+                        ),
+                        year,
+                        titles.clone(),
+                    ));
+            }
+        }
+
+        // ====== Student presenters ======
+        {
+            let titles = sessions
+                .iter()
+                .filter(|s| s.authors.iter().any(|a| a.is_student_presenter))
+                .map(|s| s.title_with_id())
+                .collect::<Vec<String>>();
+            self.items
+                .get_mut(&year)
+                .unwrap_or(&mut Vec::new())
+                .push(StatsItem::new(
+                    "Student Presenter Sessions".to_string(),
+                    titles.len() as f64,
+                    "Number of sessions presented by a student".into(),
+                    year,
+                    titles,
+                ));
+        }
+
         // Save the stats to a JSON file
         let stats_file = output_dir.join(format!("jsai_{}_stats.json", year));
         std::fs::write(stats_file.clone(), serde_json::to_string_pretty(&self)?)?;
@@ -0,0 +1,189 @@
+use crate::stats::identity::{affiliation_similarity, name_key, romanized_key};
+use crate::stats::models::{Author, Dataset};
+use anyhow::{Result, anyhow};
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Minimum affiliation similarity for a researcher record to be linked to an author of the same name.
+pub const DEFAULT_MIN_SIMILARITY: f64 = 0.5;
+
+static ORCID_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?P<orcid>\d{4}-\d{4}-\d{4}-\d{3}[\dX])").unwrap());
+
+/// One researcher of the mapping file, e.g. an export of our collaborator list or a researchmap dump.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResearcherRecord {
+    pub name: String,
+    /// Name in Latin script, e.g. `Taro Yamada`
+    #[serde(default)]
+    pub name_en: Option<String>,
+    #[serde(default)]
+    pub affiliation: Option<String>,
+    #[serde(default)]
+    pub orcid: Option<String>,
+    #[serde(default)]
+    pub researchmap_id: Option<String>,
+}
+
+/// Bare ORCID iD (`0000-0002-1825-0097`) of an iD or its `https://orcid.org/` URL.
+fn normalize_orcid(orcid: &str) -> Option<String> {
+    ORCID_REGEX
+        .captures(orcid)
+        .map(|caps| caps["orcid"].to_string())
+}
+
+/// Bare researchmap permalink of an id or its `https://researchmap.jp/` URL.
+fn normalize_researchmap_id(id: &str) -> Option<String> {
+    let id = id
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("researchmap.jp/")
+        .trim_end_matches('/');
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Read the mapping file, CSV with the header `name,name_en,affiliation,orcid,researchmap_id`
+/// or JSON Lines of the same fields when the extension is `.jsonl`.
+pub fn load_researchers(file_path: &Path) -> Result<Vec<ResearcherRecord>> {
+    let mut records: Vec<ResearcherRecord> =
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl") => std::fs::read_to_string(file_path)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| Ok(serde_json::from_str(line)?))
+                .collect::<Result<Vec<ResearcherRecord>>>()?,
+            Some("csv") => csv::Reader::from_path(file_path)?
+                .deserialize()
+                .collect::<std::result::Result<Vec<ResearcherRecord>, csv::Error>>()?,
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported mapping file {}, expected .csv or .jsonl",
+                    file_path.display()
+                ));
+            }
+        };
+    for record in records.iter_mut() {
+        record.orcid = record.orcid.as_deref().and_then(normalize_orcid);
+        record.researchmap_id = record
+            .researchmap_id
+            .as_deref()
+            .and_then(normalize_researchmap_id);
+    }
+    Ok(records)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LinkReport {
+    pub linked: usize,
+    /// Authors that already had an identifier, which is kept
+    pub already_linked: usize,
+    /// Authors matching several researchers equally well
+    pub ambiguous: Vec<String>,
+    pub unmatched: usize,
+}
+
+fn author_keys(author: &Author) -> Vec<String> {
+    let mut keys = vec![name_key(&author.name)];
+    if let Some(romanized) = &author.romanized {
+        keys.push(romanized_key(romanized));
+    }
+    keys
+}
+
+fn record_keys(record: &ResearcherRecord) -> Vec<String> {
+    let mut keys = vec![name_key(&record.name)];
+    if let Some(name_en) = &record.name_en {
+        keys.push(name_key(name_en));
+    }
+    keys
+}
+
+/// Link authors to the researchers of the mapping file by normalized name and affiliation similarity,
+/// setting `orcid` and `researchmap_id`. Identifiers already on an author are kept.
+///
+/// A link found for one occurrence is shared with the other occurrences of the same `author_id`.
+pub fn link_researcher_ids(
+    datasets: &mut [Dataset],
+    researchers: &[ResearcherRecord],
+    min_similarity: f64,
+) -> LinkReport {
+    let mut by_name: FxHashMap<String, Vec<usize>> = FxHashMap::default();
+    for (i, record) in researchers.iter().enumerate() {
+        for key in record_keys(record) {
+            let indices = by_name.entry(key).or_default();
+            if !indices.contains(&i) {
+                indices.push(i);
+            }
+        }
+    }
+
+    let mut report = LinkReport::default();
+    let mut linked_ids: FxHashMap<String, usize> = FxHashMap::default();
+    let authors = datasets
+        .iter_mut()
+        .flat_map(|dataset| dataset.sessions.iter_mut())
+        .flat_map(|session| session.authors.iter_mut());
+    let mut pending = Vec::new();
+    for author in authors {
+        if author.orcid.is_some() || author.researchmap_id.is_some() {
+            report.already_linked += 1;
+            continue;
+        }
+        let affiliation = author.affiliation();
+        let mut candidates = author_keys(author)
+            .iter()
+            .flat_map(|key| by_name.get(key).cloned().unwrap_or_default())
+            .map(|i| {
+                // without an affiliation on either side, the name alone decides
+                let similarity = match researchers[i].affiliation.as_deref() {
+                    Some(a) if !a.trim().is_empty() && !affiliation.trim().is_empty() => {
+                        affiliation_similarity(&affiliation, a)
+                    }
+                    _ => 1.0,
+                };
+                (i, similarity)
+            })
+            .filter(|(_, similarity)| *similarity >= min_similarity)
+            .collect::<Vec<(usize, f64)>>();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        candidates.dedup_by_key(|(i, _)| *i);
+
+        match candidates.as_slice() {
+            [] => pending.push(author),
+            [(_, best), (_, second), ..] if best == second => {
+                report
+                    .ambiguous
+                    .push(format!("{} ({})", author.name, affiliation));
+            }
+            [(i, _), ..] => {
+                author.orcid = researchers[*i].orcid.clone();
+                author.researchmap_id = researchers[*i].researchmap_id.clone();
+                report.linked += 1;
+                if let Some(author_id) = &author.author_id {
+                    linked_ids.insert(author_id.clone(), *i);
+                }
+            }
+        }
+    }
+
+    // other occurrences of a linked author
+    for author in pending {
+        match author
+            .author_id
+            .as_ref()
+            .and_then(|author_id| linked_ids.get(author_id))
+        {
+            Some(i) => {
+                author.orcid = researchers[*i].orcid.clone();
+                author.researchmap_id = researchers[*i].researchmap_id.clone();
+                report.linked += 1;
+            }
+            None => report.unmatched += 1,
+        }
+    }
+    report
+}
@@ -0,0 +1,223 @@
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// `<day><room><slot>[-<track><number><part>]-<order>`, e.g. `1A3-GS-2-01`, `2G4-IS-2c-05` or `3Win5-01`.
+static SESSION_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<day>\d)(?P<room>[A-Z][a-z]*)(?P<slot>\d+)(?:-(?P<track>[A-Z]{2,3})-(?P<number>\d+)(?P<part>[a-z])?)?(?:-(?P<order>\d+))?$",
+    )
+    .unwrap()
+});
+
+/// Kind of track a session belongs to, from the track code of its id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackType {
+    /// `GS`, 一般セッション
+    General,
+    /// `OS`, オーガナイズドセッション
+    Organized,
+    /// `IS`, International Session
+    International,
+    /// `KS`, 企画セッション
+    Kikaku,
+    /// `PS`, ポスターセッション
+    Poster,
+    /// `SS`, 特別セッション
+    Special,
+    /// Interactive (poster) sessions, held in the rooms `Xin`, `Yin` or `Win` with no track code
+    Interactive,
+    /// A track code not in this list
+    Other(String),
+}
+
+impl TrackType {
+    /// Track types of the research presentations that `Stats::analyze` counts: the `GS`, `OS`,
+    /// `KS`, `PS` and `SS` tracks, as before ids were parsed. International and interactive
+    /// sessions are not counted.
+    pub const PRESENTATIONS: [TrackType; 5] = [
+        TrackType::General,
+        TrackType::Organized,
+        TrackType::Kikaku,
+        TrackType::Poster,
+        TrackType::Special,
+    ];
+
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "GS" => TrackType::General,
+            "OS" => TrackType::Organized,
+            "IS" => TrackType::International,
+            "KS" => TrackType::Kikaku,
+            "PS" => TrackType::Poster,
+            "SS" => TrackType::Special,
+            _ => TrackType::Other(code.to_string()),
+        }
+    }
+
+    /// Track code in the session id, which interactive sessions do not have.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            TrackType::General => Some("GS"),
+            TrackType::Organized => Some("OS"),
+            TrackType::International => Some("IS"),
+            TrackType::Kikaku => Some("KS"),
+            TrackType::Poster => Some("PS"),
+            TrackType::Special => Some("SS"),
+            TrackType::Interactive => None,
+            TrackType::Other(code) => Some(code),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            TrackType::General => "General Session".to_string(),
+            TrackType::Organized => "Organized Session".to_string(),
+            TrackType::International => "International Session".to_string(),
+            TrackType::Kikaku => "Kikaku Session".to_string(),
+            TrackType::Poster => "Poster Session".to_string(),
+            TrackType::Special => "Special Session".to_string(),
+            TrackType::Interactive => "Interactive Session".to_string(),
+            TrackType::Other(code) => format!("{} Session", code),
+        }
+    }
+
+    pub fn is_presentation(&self) -> bool {
+        Self::PRESENTATIONS.contains(self)
+    }
+}
+
+/// A Confit session id split into its parts, e.g. `1A3-GS-2-01` is the first talk of track GS-2
+/// on day 1, in room A, slot 3.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionId {
+    pub day: u32,
+    pub room: String,
+    pub slot: u32,
+    pub track_type: TrackType,
+    /// Number of the track within its type, e.g. 2 of `GS-2`
+    pub track_number: Option<u32>,
+    /// Part of a track split over several slots, e.g. `c` of `IS-2c`
+    pub track_part: Option<char>,
+    /// Position of the session within its slot
+    pub order: Option<u32>,
+}
+
+impl SessionId {
+    /// Track of the session, e.g. `GS-2` or `IS-2c`; the room for interactive sessions.
+    pub fn track(&self) -> String {
+        match (self.track_type.code(), self.track_number) {
+            (Some(code), Some(number)) => {
+                let part = self.track_part.map(String::from).unwrap_or_default();
+                format!("{}-{}{}", code, number, part)
+            }
+            _ => self.room.clone(),
+        }
+    }
+}
+
+impl FromStr for SessionId {
+    type Err = anyhow::Error;
+
+    fn from_str(id: &str) -> Result<Self> {
+        let caps = SESSION_ID_REGEX
+            .captures(id.trim())
+            .ok_or_else(|| anyhow!("Unexpected session id: {}", id))?;
+        let room = caps["room"].to_string();
+        let track_type = match caps.name("track") {
+            Some(track) => TrackType::from_code(track.as_str()),
+            None if room.len() == 3 && room.ends_with("in") => TrackType::Interactive,
+            None => return Err(anyhow!("Session id without a track: {}", id)),
+        };
+        Ok(SessionId {
+            day: caps["day"].parse()?,
+            room,
+            slot: caps["slot"].parse()?,
+            track_type,
+            track_number: caps
+                .name("number")
+                .map(|m| m.as_str().parse())
+                .transpose()?,
+            track_part: caps.name("part").and_then(|m| m.as_str().chars().next()),
+            order: caps.name("order").map(|m| m.as_str().parse()).transpose()?,
+        })
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.day, self.room, self.slot)?;
+        if self.track_type.code().is_some() && self.track_number.is_some() {
+            write!(f, "-{}", self.track())?;
+        }
+        if let Some(order) = self.order {
+            write!(f, "-{:02}", order)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        for id in [
+            "1A3-GS-2-01",
+            "2G4-IS-2c-05",
+            "3Win5-01",
+            "4Xin1-12",
+            "2K4-OS-5-02",
+            "1B2-KS-10-03",
+            "3L1-XX-1-01",
+        ] {
+            let parsed = id.parse::<SessionId>().unwrap();
+            assert_eq!(parsed.to_string(), id);
+        }
+    }
+
+    #[test]
+    fn ids_are_split_into_their_parts() {
+        let id = "2G4-IS-2c-05".parse::<SessionId>().unwrap();
+        assert_eq!(
+            id,
+            SessionId {
+                day: 2,
+                room: "G".to_string(),
+                slot: 4,
+                track_type: TrackType::International,
+                track_number: Some(2),
+                track_part: Some('c'),
+                order: Some(5),
+            }
+        );
+        assert_eq!(id.track(), "IS-2c");
+
+        let id = "3Win5-01".parse::<SessionId>().unwrap();
+        assert_eq!(id.track_type, TrackType::Interactive);
+        assert_eq!(id.track(), "Win");
+
+        let id = "3L1-XX-1-01".parse::<SessionId>().unwrap();
+        assert_eq!(id.track_type, TrackType::Other("XX".to_string()));
+    }
+
+    #[test]
+    fn unexpected_ids_are_rejected() {
+        for id in ["", "A1-GS-1-01", "1A3", "2A1-Invited-01", "1A3-GS-01-x"] {
+            assert!(id.parse::<SessionId>().is_err(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn international_and_interactive_sessions_are_not_counted_as_presentations() {
+        assert!(TrackType::General.is_presentation());
+        assert!(!TrackType::International.is_presentation());
+        assert!(!TrackType::Interactive.is_presentation());
+        assert!(!TrackType::Other("XX".to_string()).is_presentation());
+    }
+}
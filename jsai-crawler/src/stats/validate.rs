@@ -0,0 +1,252 @@
+use crate::stats::models::{Dataset, Session};
+use clap::ValueEnum;
+use fxhash::{FxHashMap, FxHashSet};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Number of offending sessions or sections shown per check.
+const MAX_EXAMPLES: usize = 5;
+
+/// Keywords longer than this many characters are probably several keywords in one.
+const MAX_KEYWORD_CHARS: usize = 40;
+
+/// Forms of session ids that are expected although they do not parse into a
+/// [`SessionId`](crate::stats::tracks::SessionId).
+const LEGACY_SESSION_ID_FORMS: &[&str] = &[
+    // invited talks, e.g. `2A1-Invited-01`
+    r"[1-9][A-Z][a-z]*\d+-Invited-\d{2}",
+];
+static LEGACY_SESSION_ID_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(&format!("^(?:{})$", LEGACY_SESSION_ID_FORMS.join("|"))).unwrap());
+
+/// Separators that should have split a keyword field into several keywords.
+static KEYWORD_SEPARATOR_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"[,，、;；]").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum LintCheck {
+    DuplicateIds,
+    DuplicateUrls,
+    EmptyTitles,
+    EmptyAbstracts,
+    NoAuthors,
+    InvalidIds,
+    EmptySections,
+    UnsplitKeywords,
+    UnloadedPages,
+}
+
+impl LintCheck {
+    pub const ALL: [LintCheck; 9] = [
+        LintCheck::DuplicateIds,
+        LintCheck::DuplicateUrls,
+        LintCheck::EmptyTitles,
+        LintCheck::EmptyAbstracts,
+        LintCheck::NoAuthors,
+        LintCheck::InvalidIds,
+        LintCheck::EmptySections,
+        LintCheck::UnsplitKeywords,
+        LintCheck::UnloadedPages,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LintCheck::DuplicateIds => "Duplicate session ids",
+            LintCheck::DuplicateUrls => "Duplicate session URLs",
+            LintCheck::EmptyTitles => "Empty titles",
+            LintCheck::EmptyAbstracts => "Empty abstracts",
+            LintCheck::NoAuthors => "Sessions without authors",
+            LintCheck::InvalidIds => "Unexpected session ids",
+            LintCheck::EmptySections => "Sections without sessions",
+            LintCheck::UnsplitKeywords => "Unsplit keywords",
+            LintCheck::UnloadedPages => "Section pages that did not load",
+        }
+    }
+
+    /// Highest tolerated share of offending sessions (of sections for `EmptySections` and
+    /// `UnloadedPages`).
+    /// Some sessions, e.g. keynotes, legitimately have no abstract or authors.
+    pub fn default_threshold(&self) -> f64 {
+        match self {
+            LintCheck::EmptyAbstracts => 0.05,
+            LintCheck::NoAuthors => 0.05,
+            LintCheck::EmptySections => 0.05,
+            LintCheck::UnsplitKeywords => 0.01,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Parse a `--threshold` value such as `no-authors=0.1`.
+pub fn parse_threshold(value: &str) -> Result<(LintCheck, f64), String> {
+    let (name, threshold) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected <check>=<ratio>, got {}", value))?;
+    let check = LintCheck::from_str(name.trim(), true)?;
+    let threshold = threshold
+        .trim()
+        .parse::<f64>()
+        .map_err(|e| format!("invalid ratio {}: {}", threshold, e))?;
+    Ok((check, threshold))
+}
+
+#[derive(Debug, Clone)]
+pub struct LintResult {
+    pub check: LintCheck,
+    pub count: usize,
+    /// Number of sessions, or of sections for `EmptySections` and `UnloadedPages`
+    pub total: usize,
+    pub threshold: f64,
+    pub examples: Vec<String>,
+}
+
+impl LintResult {
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.count as f64 / self.total as f64
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        self.ratio() > self.threshold
+    }
+}
+
+/// Sessions sharing a key with an earlier session.
+fn duplicates(sessions: &[Session], key: impl Fn(&Session) -> String) -> Vec<&Session> {
+    let mut seen = FxHashSet::default();
+    sessions
+        .iter()
+        .filter(|session| !seen.insert(key(session)))
+        .collect()
+}
+
+fn is_unsplit_keyword(keyword: &str) -> bool {
+    KEYWORD_SEPARATOR_REGEX.is_match(keyword) || keyword.chars().count() > MAX_KEYWORD_CHARS
+}
+
+/// Run every check on the dataset. `thresholds` overrides the default threshold of a check.
+pub fn lint_dataset(dataset: &Dataset, thresholds: &FxHashMap<LintCheck, f64>) -> Vec<LintResult> {
+    let sessions = &dataset.sessions;
+    let describe = |session: &Session| format!("{} {}", session.id, session.url);
+
+    LintCheck::ALL
+        .iter()
+        .map(|check| {
+            let offending: Vec<String> = match check {
+                LintCheck::DuplicateIds => {
+                    duplicates(sessions, |s| format!("{}{}/{}", s.event, s.year, s.id))
+                        .into_iter()
+                        .map(describe)
+                        .collect()
+                }
+                LintCheck::DuplicateUrls => duplicates(sessions, |s| s.url.clone())
+                    .into_iter()
+                    .map(describe)
+                    .collect(),
+                LintCheck::EmptyTitles => sessions
+                    .iter()
+                    .filter(|s| s.title.trim().is_empty())
+                    .map(describe)
+                    .collect(),
+                LintCheck::EmptyAbstracts => sessions
+                    .iter()
+                    .filter(|s| s.abstract_text.trim().is_empty())
+                    .map(describe)
+                    .collect(),
+                LintCheck::NoAuthors => sessions
+                    .iter()
+                    .filter(|s| s.authors.is_empty())
+                    .map(describe)
+                    .collect(),
+                LintCheck::InvalidIds => sessions
+                    .iter()
+                    .filter(|s| s.parsed_id().is_err() && !LEGACY_SESSION_ID_REGEX.is_match(&s.id))
+                    .map(describe)
+                    .collect(),
+                LintCheck::EmptySections => {
+                    let used = sessions
+                        .iter()
+                        .map(|s| s.section.url.as_str())
+                        .collect::<FxHashSet<&str>>();
+                    dataset
+                        .sections
+                        .iter()
+                        .filter(|section| !used.contains(section.url.as_str()))
+                        .map(|section| format!("{} {}", section.id, section.url))
+                        .collect()
+                }
+                LintCheck::UnsplitKeywords => sessions
+                    .iter()
+                    .filter(|s| s.keywords.iter().any(|k| is_unsplit_keyword(k)))
+                    .map(|s| format!("{} {}", s.id, s.keywords.join(" / ")))
+                    .collect(),
+                LintCheck::UnloadedPages => dataset.unloaded_pages.clone(),
+            };
+            let total = match check {
+                LintCheck::EmptySections => dataset.sections.len(),
+                // a crawl that stopped on its first page may have no sections at all
+                LintCheck::UnloadedPages => dataset.sections.len().max(offending.len()),
+                _ => sessions.len(),
+            };
+            LintResult {
+                check: *check,
+                count: offending.len(),
+                total,
+                threshold: thresholds
+                    .get(check)
+                    .copied()
+                    .unwrap_or_else(|| check.default_threshold()),
+                examples: offending.into_iter().take(MAX_EXAMPLES).collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Number of `ids` flagged by the `InvalidIds` check.
+    fn invalid_ids(ids: &[&str]) -> usize {
+        let sessions = ids
+            .iter()
+            .map(|id| Session {
+                id: id.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let dataset = Dataset::new(Vec::new(), sessions);
+        lint_dataset(&dataset, &FxHashMap::default())
+            .into_iter()
+            .find(|result| result.check == LintCheck::InvalidIds)
+            .unwrap()
+            .count
+    }
+
+    #[test]
+    fn parsed_and_legacy_ids_are_accepted() {
+        assert_eq!(
+            invalid_ids(&["1A3-GS-2-01", "2G4-IS-2c-05", "3Win5-01", "2A1-Invited-01"]),
+            0
+        );
+    }
+
+    #[test]
+    fn near_miss_ids_are_flagged() {
+        let ids = [
+            "2A1-Invited",
+            "2A1-invited-01",
+            "2A1-Invited-01-02",
+            "2a1-Invited-01",
+            "2A1-Invited-1",
+            "2A1-Keynote-01",
+            "1A3-GS-2-01x",
+            "A1-GS-1-01",
+        ];
+        for id in ids {
+            assert_eq!(invalid_ids(&[id]), 1, "{}", id);
+        }
+    }
+}